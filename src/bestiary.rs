@@ -0,0 +1,517 @@
+use std::sync::OnceLock;
+
+use crate::clock::Phase;
+use crate::combat::Creature;
+use crate::content::{self, ContentError, Entry, Section};
+use crate::item::Item;
+use crate::rng::Rng;
+use crate::world::{TileName, World};
+
+// every creature, encounter table, region and difficulty, loaded once at startup by `load_bestiary`
+static BESTIARY: OnceLock<Bestiary> = OnceLock::new();
+
+// the difficulty new games start on, must be defined in the bestiary
+pub const DEFAULT_DIFFICULTY: &str = "normal";
+
+#[derive(Clone, Debug)]
+pub struct CreatureDef {
+    pub id: String,
+    pub name: String,
+    pub plural: String,
+    pub health: i32,
+    pub attack: u8,
+    pub armor: u8,
+    // roll on a d20 the player has to reach to hit it
+    pub evasion: i32,
+    // catalogue id and percent chance of it being dropped
+    pub loot: Vec<(String, i32)>,
+    // lowest and highest gold carried
+    pub gold: (i32, i32),
+    // experience for defeating it
+    pub xp: u32,
+    // percent chance a hit poisons and for how many turns
+    pub poison: Option<(i32, u32)>,
+}
+
+impl CreatureDef {
+    pub fn matches(&self, name: &str) -> bool {
+        return self.id.eq_ignore_ascii_case(name)
+            || self.name.eq_ignore_ascii_case(name)
+            || self.plural.eq_ignore_ascii_case(name);
+    }
+}
+
+// what can turn up on one kind of tile
+#[derive(Clone, Debug)]
+pub struct EncounterTable {
+    pub tile: TileName,
+    // only used at this time of day, tables without one cover the rest
+    pub phase: Option<Phase>,
+    // percent chance per move
+    pub rate: i32,
+    // creature id and weight
+    pub creatures: Vec<(String, i32)>,
+}
+
+// an area of the map where encounters are more or less likely
+#[derive(Clone, Debug)]
+pub struct Region {
+    pub id: String,
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+    // percent of the usual encounter rate
+    pub encounters: i32,
+}
+
+impl Region {
+    fn contains(&self, x: usize, y: usize) -> bool {
+        return x >= self.from.0 && x <= self.to.0 && y >= self.from.1 && y <= self.to.1;
+    }
+}
+
+// percentages applied to encounter rates and creature stats
+#[derive(Clone, Debug)]
+pub struct Difficulty {
+    pub id: String,
+    pub encounters: i32,
+    pub health: i32,
+    pub attack: i32,
+}
+
+#[derive(Debug, Default)]
+struct Bestiary {
+    creatures: Vec<CreatureDef>,
+    tables: Vec<EncounterTable>,
+    regions: Vec<Region>,
+    difficulties: Vec<Difficulty>,
+}
+
+// reads a number that can't be negative
+fn number(entry: &Entry, value: &str) -> Result<i32, ContentError> {
+    match value.parse() {
+        Ok(value) if value >= 0 => return Ok(value),
+        _ => return Err(ContentError::InvalidValue(entry.line, value.to_string())),
+    }
+}
+
+fn require(section: &Section, key: &str) -> Result<i32, ContentError> {
+    let entry = section.require(key)?;
+    return number(entry, &entry.value);
+}
+
+// `<name> [number]`, number defaulting to `default`
+fn parse_weighted(entry: &Entry, default: i32) -> Result<(String, i32), ContentError> {
+    let args: Vec<&str> = entry.value.split_whitespace().collect();
+    match args.as_slice() {
+        [name] => return Ok((name.to_string(), default)),
+        [name, value] => return Ok((name.to_string(), number(entry, value)?)),
+        _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+    }
+}
+
+fn parse_creature(section: &Section, errors: &mut Vec<ContentError>) -> Option<CreatureDef> {
+    let error_count = errors.len();
+
+    let mut loot = vec!();
+    for entry in section.all("loot") {
+        match parse_weighted(entry, 100) {
+            Ok((name, chance)) => match Item::lookup(&name) {
+                Some(item) => loot.push((item.id, chance)),
+                None => errors.push(ContentError::UnknownItem(entry.line, name)),
+            },
+            Err(err) => errors.push(err),
+        }
+    }
+
+    let gold = match section.get("gold") {
+        Some(entry) => {
+            let args: Vec<&str> = entry.value.split_whitespace().collect();
+            match args.as_slice() {
+                [low, high] => match (number(entry, low), number(entry, high)) {
+                    (Ok(low), Ok(high)) if high >= low => (low, high),
+                    _ => {
+                        errors.push(ContentError::InvalidValue(entry.line, entry.value.clone()));
+                        (0, 0)
+                    },
+                },
+                _ => {
+                    errors.push(ContentError::InvalidValue(entry.line, entry.value.clone()));
+                    (0, 0)
+                },
+            }
+        },
+        None => (0, 0),
+    };
+
+    let poison = match section.get("poison") {
+        Some(entry) => {
+            let args: Vec<&str> = entry.value.split_whitespace().collect();
+            match args.as_slice() {
+                [chance, turns] => match (number(entry, chance), number(entry, turns)) {
+                    (Ok(chance), Ok(turns)) if turns > 0 => Some((chance, turns as u32)),
+                    _ => {
+                        errors.push(ContentError::InvalidValue(entry.line, entry.value.clone()));
+                        None
+                    },
+                },
+                _ => {
+                    errors.push(ContentError::InvalidValue(entry.line, entry.value.clone()));
+                    None
+                },
+            }
+        },
+        None => None,
+    };
+
+    let name = section.require("name").map(|e| e.value.clone());
+    let health = require(section, "health");
+    let attack = require(section, "attack");
+    let evasion = require(section, "evasion");
+    let armor = section.get("armor").map_or(Ok(0), |e| number(e, &e.value));
+    let xp = section.get("xp").map(|e| number(e, &e.value)).transpose();
+
+    match (name, health, attack, evasion, armor, xp) {
+        (Ok(name), Ok(health), Ok(attack), Ok(evasion), Ok(armor), Ok(xp)) if errors.len() == error_count => {
+            if health == 0 {
+                errors.push(ContentError::InvalidValue(section.line, "health = 0".to_string()));
+                return None;
+            }
+            return Some(CreatureDef {
+                id: section.id.clone(),
+                plural: section.get("plural").map_or(format!("{}s", name.to_lowercase()), |e| e.value.clone()),
+                name,
+                health,
+                attack: attack.min(u8::MAX as i32) as u8,
+                armor: armor.min(u8::MAX as i32) as u8,
+                evasion,
+                loot,
+                gold,
+                // tougher creatures are worth more
+                xp: xp.unwrap_or(health / 2 + attack) as u32,
+                poison,
+            });
+        },
+        (name, health, attack, evasion, armor, xp) => {
+            errors.extend(name.err());
+            errors.extend(health.err());
+            errors.extend(attack.err());
+            errors.extend(evasion.err());
+            errors.extend(armor.err());
+            errors.extend(xp.err());
+            return None;
+        },
+    }
+}
+
+fn parse_table(section: &Section, creatures: &[CreatureDef], errors: &mut Vec<ContentError>) -> Option<EncounterTable> {
+    let tile = TileName::parse(&section.id)
+        .ok_or(ContentError::UnknownTile(section.line, section.id.clone()))
+        .map_err(|err| errors.push(err))
+        .ok()?;
+    let rate = require(section, "rate").map_err(|err| errors.push(err)).ok()?;
+    let phase = match section.get("phase") {
+        Some(entry) => Some(Phase::parse(&entry.value)
+            .ok_or(ContentError::InvalidValue(entry.line, entry.value.clone()))
+            .map_err(|err| errors.push(err))
+            .ok()?),
+        None => None,
+    };
+
+    let mut table = vec!();
+    for entry in section.all("creature") {
+        match parse_weighted(entry, 1) {
+            Ok((id, _)) if !creatures.iter().any(|c| c.id == id) => {
+                errors.push(ContentError::UnknownCreature(entry.line, id));
+            },
+            Ok(found) => table.push(found),
+            Err(err) => errors.push(err),
+        }
+    }
+    if section.get("creature").is_none() {
+        errors.push(ContentError::MissingKey(section.line, "creature".to_string()));
+        return None;
+    }
+
+    return Some(EncounterTable {
+        tile,
+        phase,
+        rate,
+        creatures: table,
+    });
+}
+
+fn parse_corner(section: &Section, key: &str, world: &World) -> Result<(usize, usize), ContentError> {
+    let entry = section.require(key)?;
+    let args: Vec<&str> = entry.value.split_whitespace().collect();
+    match args.as_slice() {
+        [x, y] => match (x.parse::<i32>(), y.parse::<i32>()) {
+            (Ok(x), Ok(y)) if world.in_bounds(x, y) => return Ok((x as usize, y as usize)),
+            _ => return Err(ContentError::UnknownTile(entry.line, entry.value.clone())),
+        },
+        _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+    }
+}
+
+fn parse_region(section: &Section, world: &World, errors: &mut Vec<ContentError>) -> Option<Region> {
+    let from = parse_corner(section, "from", world);
+    let to = parse_corner(section, "to", world);
+    let encounters = require(section, "encounters");
+
+    match (from, to, encounters) {
+        (Ok(from), Ok(to), Ok(encounters)) => return Some(Region {
+            id: section.id.clone(),
+            // either pair of opposite corners will do
+            from: (from.0.min(to.0), from.1.min(to.1)),
+            to: (from.0.max(to.0), from.1.max(to.1)),
+            encounters,
+        }),
+        (from, to, encounters) => {
+            errors.extend(from.err());
+            errors.extend(to.err());
+            errors.extend(encounters.err());
+            return None;
+        },
+    }
+}
+
+fn parse_difficulty(section: &Section, errors: &mut Vec<ContentError>) -> Option<Difficulty> {
+    let encounters = require(section, "encounters");
+    let health = require(section, "health");
+    let attack = require(section, "attack");
+
+    match (encounters, health, attack) {
+        (Ok(encounters), Ok(health), Ok(attack)) => return Some(Difficulty {
+            id: section.id.clone(),
+            encounters,
+            health,
+            attack,
+        }),
+        (encounters, health, attack) => {
+            errors.extend(encounters.err());
+            errors.extend(health.err());
+            errors.extend(attack.err());
+            return None;
+        },
+    }
+}
+
+// loads creatures, encounter tables, regions and difficulties, must run after the item catalogue
+pub fn load_bestiary(path: &str, world: &World) -> Result<(), Vec<ContentError>> {
+    let sections = content::load(path).map_err(|err| vec!(err))?;
+    let mut errors = vec!();
+    let mut bestiary = Bestiary::default();
+
+    // creatures go first so tables can name ones defined further down
+    for section in sections.iter().filter(|s| s.kind == "creature") {
+        if bestiary.creatures.iter().any(|c| c.id == section.id) {
+            errors.push(ContentError::Duplicate(section.line, section.id.clone()));
+            continue;
+        }
+        if let Some(creature) = parse_creature(section, &mut errors) {
+            bestiary.creatures.push(creature);
+        }
+    }
+
+    for section in sections.iter() {
+        match section.kind.as_str() {
+            "creature" => {},
+            "encounters" => {
+                if let Some(table) = parse_table(section, &bestiary.creatures, &mut errors) {
+                    if bestiary.tables.iter().any(|t| t.tile == table.tile && t.phase == table.phase) {
+                        errors.push(ContentError::Duplicate(section.line, section.id.clone()));
+                        continue;
+                    }
+                    bestiary.tables.push(table);
+                }
+            },
+            "region" => {
+                if bestiary.regions.iter().any(|r| r.id == section.id) {
+                    errors.push(ContentError::Duplicate(section.line, section.id.clone()));
+                    continue;
+                }
+                if let Some(region) = parse_region(section, world, &mut errors) {
+                    bestiary.regions.push(region);
+                }
+            },
+            "difficulty" => {
+                if bestiary.difficulties.iter().any(|d| d.id == section.id) {
+                    errors.push(ContentError::Duplicate(section.line, section.id.clone()));
+                    continue;
+                }
+                if let Some(difficulty) = parse_difficulty(section, &mut errors) {
+                    bestiary.difficulties.push(difficulty);
+                }
+            },
+            _ => errors.push(ContentError::InvalidValue(section.line, section.kind.clone())),
+        }
+    }
+
+    if errors.is_empty() && !bestiary.difficulties.iter().any(|d| d.id == DEFAULT_DIFFICULTY) {
+        errors.push(ContentError::MissingKey(0, format!("difficulty {}", DEFAULT_DIFFICULTY)));
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    BESTIARY.set(bestiary).expect("bestiary loaded twice");
+    return Ok(());
+}
+
+// loads the real bestiary once, however many tests ask for it
+#[cfg(test)]
+pub fn load_test_bestiary() {
+    static LOADED: std::sync::Once = std::sync::Once::new();
+    crate::item::load_test_catalogue();
+    LOADED.call_once(|| load_bestiary("./src/data/creatures.txt", &World::test_map()).unwrap());
+}
+
+fn bestiary() -> &'static Bestiary {
+    return BESTIARY.get().expect("bestiary not loaded");
+}
+
+// looks a creature up by id, name or plural
+pub fn find(name: &str) -> Option<&'static CreatureDef> {
+    return bestiary().creatures.iter().find(|c| c.matches(name));
+}
+
+// the creatures that can turn up on tiles of this kind
+pub fn habitat(tile: &TileName) -> Vec<&'static CreatureDef> {
+    return bestiary().tables.iter()
+        .filter(|t| t.tile == *tile)
+        .flat_map(|t| t.creatures.iter())
+        .filter_map(|(id, _)| find(id))
+        .collect();
+}
+
+pub fn difficulty(id: &str) -> Option<&'static Difficulty> {
+    return bestiary().difficulties.iter().find(|d| d.id.eq_ignore_ascii_case(id));
+}
+
+pub fn difficulties() -> &'static [Difficulty] {
+    return &bestiary().difficulties;
+}
+
+// the table for this tile at the current time of day
+fn table(world: &World, x: usize, y: usize) -> Option<&'static EncounterTable> {
+    let tile = world.tile_name(x, y);
+    let phase = world.phase();
+    let tables = &bestiary().tables;
+    return tables.iter().find(|t| t.tile == tile && t.phase == Some(phase))
+        .or_else(|| tables.iter().find(|t| t.tile == tile && t.phase.is_none()));
+}
+
+// percent chance of an encounter on each move onto this tile
+pub fn encounter_rate(world: &World, x: usize, y: usize, difficulty: &Difficulty) -> i32 {
+    let mut rate = match table(world, x, y) {
+        Some(table) => table.rate,
+        None => return 0,
+    };
+    // overlapping regions stack
+    for region in bestiary().regions.iter().filter(|r| r.contains(x, y)) {
+        rate = rate * region.encounters / 100;
+    }
+    return rate * difficulty.encounters / 100;
+}
+
+// rolls whether something attacks the player on this tile, `avoided` is the percent of encounters the player steers clear of
+pub fn encounter(world: &World, x: usize, y: usize, difficulty: &Difficulty, avoided: i32, rng: &mut Rng) -> Option<Creature> {
+    if rng.range(1, 100) > encounter_rate(world, x, y, difficulty) * (100 - avoided) / 100 {
+        return None;
+    }
+
+    let table = table(world, x, y)?;
+    let total: i32 = table.creatures.iter().map(|(_, weight)| weight).sum();
+    if total == 0 {
+        return None;
+    }
+    let mut roll = rng.range(1, total);
+    let (id, _) = table.creatures.iter().find(|(_, weight)| {
+        roll -= weight;
+        roll <= 0
+    })?;
+
+    return Some(Creature::spawn(find(id)?, difficulty, rng));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item;
+
+    fn section(src: &str) -> Section {
+        item::load_test_catalogue();
+        return content::parse(src).unwrap().remove(0);
+    }
+
+    #[test]
+    fn creatures_fill_in_defaults() {
+        let mut errors = vec!();
+        let rat = parse_creature(&section("[creature rat]\nname = Rat\nhealth = 4\nattack = 2\nevasion = 5\nloot = hide\n"), &mut errors).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!((rat.plural.as_str(), rat.armor, rat.xp), ("rats", 0, 4));
+        assert_eq!(rat.loot, vec!(("hide".to_string(), 100)));
+        assert_eq!((rat.gold, rat.poison), ((0, 0), None));
+        assert!(rat.matches("RATS"));
+    }
+
+    #[test]
+    fn creatures_report_every_bad_value() {
+        let mut errors = vec!();
+        let creature = parse_creature(&section("[creature bad]\nname = Bad\nhealth = -1\nevasion = 5\ngold = 9 2\nloot = anvil\npoison = 50 0\n"), &mut errors);
+        assert!(creature.is_none());
+        assert_eq!(errors, vec!(
+            ContentError::UnknownItem(6, "anvil".to_string()),
+            ContentError::InvalidValue(5, "9 2".to_string()),
+            ContentError::InvalidValue(7, "50 0".to_string()),
+            ContentError::InvalidValue(3, "-1".to_string()),
+            ContentError::MissingKey(1, "attack".to_string()),
+        ));
+    }
+
+    #[test]
+    fn creatures_need_health() {
+        let mut errors = vec!();
+        assert!(parse_creature(&section("[creature ghost]\nname = Ghost\nhealth = 0\nattack = 1\nevasion = 1\n"), &mut errors).is_none());
+        assert_eq!(errors, vec!(ContentError::InvalidValue(1, "health = 0".to_string())));
+    }
+
+    #[test]
+    fn tables_name_known_creatures() {
+        let mut errors = vec!();
+        let rats = vec!(parse_creature(&section("[creature rat]\nname = Rat\nhealth = 4\nattack = 2\nevasion = 5\n"), &mut errors).unwrap());
+
+        let table = parse_table(&section("[encounters forest]\nphase = night\nrate = 10\ncreature = rat 3\ncreature = wolf\n"), &rats, &mut errors).unwrap();
+        assert_eq!((table.tile, table.phase, table.rate), (TileName::Forest, Some(Phase::Night), 10));
+        assert_eq!(table.creatures, vec!(("rat".to_string(), 3)));
+        assert_eq!(errors, vec!(ContentError::UnknownCreature(5, "wolf".to_string())));
+
+        assert!(parse_table(&section("[encounters lava]\nrate = 10\ncreature = rat\n"), &rats, &mut errors).is_none());
+        assert!(parse_table(&section("[encounters hills]\nrate = 10\nphase = noon\ncreature = rat\n"), &rats, &mut errors).is_none());
+        assert!(parse_table(&section("[encounters hills]\nrate = 10\n"), &rats, &mut errors).is_none());
+        assert_eq!(errors[1..], [
+            ContentError::UnknownTile(1, "lava".to_string()),
+            ContentError::InvalidValue(3, "noon".to_string()),
+            ContentError::MissingKey(1, "creature".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn regions_accept_corners_either_way_round() {
+        let mut errors = vec!();
+        let world = World::test_map();
+        let region = parse_region(&section("[region woods]\nfrom = 30 20\nto = 10 5\nencounters = 50\n"), &world, &mut errors).unwrap();
+        assert_eq!((region.from, region.to), ((10, 5), (30, 20)));
+        assert!(region.contains(10, 20) && !region.contains(31, 20));
+
+        assert!(parse_region(&section("[region off]\nfrom = 0 0\nto = 10 5\nencounters = 50\n"), &world, &mut errors).is_none());
+        assert_eq!(errors, vec!(ContentError::UnknownTile(2, "0 0".to_string())));
+    }
+
+    #[test]
+    fn difficulties_need_every_percentage() {
+        let mut errors = vec!();
+        assert!(parse_difficulty(&section("[difficulty hard]\nencounters = 150\nhealth = 120\nattack = 120\n"), &mut errors).is_some());
+        assert!(parse_difficulty(&section("[difficulty odd]\nencounters = 150\nhealth = x\n"), &mut errors).is_none());
+        assert_eq!(errors, vec!(ContentError::InvalidValue(3, "x".to_string()), ContentError::MissingKey(1, "attack".to_string())));
+    }
+}
@@ -0,0 +1,292 @@
+use std::fmt::{self, Display, Formatter};
+use std::sync::OnceLock;
+
+use crate::content::{self, ContentError, Entry, Section, Writer};
+use crate::item::Item;
+
+// every class a new character can pick, loaded once at startup by `load_classes`
+static CLASSES: OnceLock<Vec<ClassDef>> = OnceLock::new();
+
+// points a new character spreads over their attributes on top of their class
+pub const ATTRIBUTE_POINTS: u8 = 4;
+pub const MAX_ATTRIBUTE: u8 = 6;
+// attributes can be trained past what character creation allows, up to this
+pub const MAX_TRAINED: u8 = 10;
+pub const MAX_LEVEL: u8 = 10;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Attribute {
+    // carry capacity and damage
+    Strength,
+    // dodging blows and getting away
+    Agility,
+    // health
+    Endurance,
+}
+
+impl Attribute {
+    pub const ALL: [Attribute; 3] = [Attribute::Strength, Attribute::Agility, Attribute::Endurance];
+
+    pub fn parse(value: &str) -> Option<Attribute> {
+        return Attribute::ALL.into_iter().find(|a| {
+            let name = a.to_string();
+            // "str" works as well as "strength"
+            value.len() >= 3 && name.starts_with(&value.to_lowercase())
+        });
+    }
+}
+
+impl Display for Attribute {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use Attribute::*;
+        match self {
+            Strength => write!(f, "strength"),
+            Agility => write!(f, "agility"),
+            Endurance => write!(f, "endurance"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Attributes {
+    pub strength: u8,
+    pub agility: u8,
+    pub endurance: u8,
+}
+
+impl Attributes {
+    pub fn get(&self, attribute: Attribute) -> u8 {
+        match attribute {
+            Attribute::Strength => return self.strength,
+            Attribute::Agility => return self.agility,
+            Attribute::Endurance => return self.endurance,
+        }
+    }
+
+    pub fn get_mut(&mut self, attribute: Attribute) -> &mut u8 {
+        match attribute {
+            Attribute::Strength => return &mut self.strength,
+            Attribute::Agility => return &mut self.agility,
+            Attribute::Endurance => return &mut self.endurance,
+        }
+    }
+
+    pub fn save(&self, out: &mut Writer) {
+        for attribute in Attribute::ALL {
+            out.entry(&attribute.to_string(), self.get(attribute));
+        }
+    }
+
+    // reads every attribute from a section
+    pub fn load(section: &Section) -> Result<Attributes, ContentError> {
+        let mut attributes = Attributes::default();
+        for attribute in Attribute::ALL {
+            let entry = section.require(&attribute.to_string())?;
+            *attributes.get_mut(attribute) = parse_attribute(entry, MAX_TRAINED)?;
+        }
+        return Ok(attributes);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ClassDef {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub attributes: Attributes,
+    pub gold: u32,
+    // carried from the start
+    pub items: Vec<Item>,
+    // worn from the start
+    pub gear: Vec<Item>,
+}
+
+impl ClassDef {
+    pub fn matches(&self, name: &str) -> bool {
+        return self.id.eq_ignore_ascii_case(name) || self.name.eq_ignore_ascii_case(name);
+    }
+}
+
+fn parse_attribute(entry: &Entry, max: u8) -> Result<u8, ContentError> {
+    match entry.value.parse() {
+        Ok(value) if (1..=max).contains(&value) => return Ok(value),
+        _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+    }
+}
+
+fn parse_class(section: &Section, errors: &mut Vec<ContentError>) -> Option<ClassDef> {
+    let error_count = errors.len();
+
+    let mut attributes = Attributes::default();
+    for attribute in Attribute::ALL {
+        match section.require(&attribute.to_string()).and_then(|entry| parse_attribute(entry, MAX_ATTRIBUTE)) {
+            Ok(value) => *attributes.get_mut(attribute) = value,
+            Err(err) => errors.push(err),
+        }
+    }
+
+    let mut items = vec!();
+    for entry in section.all("item") {
+        match Item::from_entry(entry) {
+            Ok(item) => items.push(item),
+            Err(err) => errors.push(err),
+        }
+    }
+
+    let mut gear: Vec<Item> = vec!();
+    for entry in section.all("equip") {
+        match Item::lookup(&entry.value) {
+            Some(item) if item.def().slot.is_none() => errors.push(ContentError::InvalidValue(entry.line, entry.value.clone())),
+            Some(item) if gear.iter().any(|g| g.def().slot == item.def().slot) => {
+                errors.push(ContentError::Duplicate(entry.line, entry.value.clone()));
+            },
+            Some(item) => gear.push(item),
+            None => errors.push(ContentError::UnknownItem(entry.line, entry.value.clone())),
+        }
+    }
+
+    let gold = match section.get("gold") {
+        Some(entry) => match entry.value.parse() {
+            Ok(gold) => gold,
+            Err(_) => {
+                errors.push(ContentError::InvalidValue(entry.line, entry.value.clone()));
+                0
+            },
+        },
+        None => 0,
+    };
+
+    let name = section.require("name").map_err(|err| errors.push(err)).ok()?;
+    let description = section.require("description").map_err(|err| errors.push(err)).ok()?;
+    if errors.len() > error_count {
+        return None;
+    }
+
+    return Some(ClassDef {
+        id: section.id.clone(),
+        name: name.value.clone(),
+        description: description.value.clone(),
+        attributes,
+        gold,
+        items,
+        gear,
+    });
+}
+
+// loads every class, must run after the item catalogue
+pub fn load_classes(path: &str) -> Result<(), Vec<ContentError>> {
+    let sections = content::load(path).map_err(|err| vec!(err))?;
+    let mut errors = vec!();
+    let mut classes: Vec<ClassDef> = vec!();
+
+    for section in sections.iter() {
+        if section.kind != "class" {
+            errors.push(ContentError::InvalidValue(section.line, section.kind.clone()));
+            continue;
+        }
+        if classes.iter().any(|c| c.id == section.id) {
+            errors.push(ContentError::Duplicate(section.line, section.id.clone()));
+            continue;
+        }
+        if let Some(class) = parse_class(section, &mut errors) {
+            classes.push(class);
+        }
+    }
+
+    if errors.is_empty() && classes.is_empty() {
+        errors.push(ContentError::MissingKey(0, "class".to_string()));
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    CLASSES.set(classes).expect("classes loaded twice");
+    return Ok(());
+}
+
+// loads the real classes once, however many tests ask for them
+#[cfg(test)]
+pub fn load_test_classes() {
+    static LOADED: std::sync::Once = std::sync::Once::new();
+    crate::item::load_test_catalogue();
+    LOADED.call_once(|| load_classes("./src/data/classes.txt").unwrap());
+}
+
+pub fn classes() -> &'static [ClassDef] {
+    return CLASSES.get().expect("classes not loaded");
+}
+
+pub fn find_class(name: &str) -> Option<&'static ClassDef> {
+    return classes().iter().find(|c| c.matches(name));
+}
+
+// total experience needed to reach `level`
+pub fn xp_for_level(level: u8) -> u32 {
+    let level = level as u32;
+    return 50 * level * level.saturating_sub(1);
+}
+
+// abilities that grow by being used
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Skill {
+    // finds more when gathering
+    Foraging,
+    // runs into fewer creatures
+    Navigation,
+    // crosses rivers more safely
+    Swimming,
+}
+
+impl Skill {
+    pub const ALL: [Skill; 3] = [Skill::Foraging, Skill::Navigation, Skill::Swimming];
+}
+
+impl Display for Skill {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use Skill::*;
+        match self {
+            Foraging => write!(f, "foraging"),
+            Navigation => write!(f, "navigation"),
+            Swimming => write!(f, "swimming"),
+        }
+    }
+}
+
+// uses needed to reach each skill level
+const SKILL_STEPS: [u32; 5] = [5, 15, 30, 50, 80];
+
+// how often each skill has been used
+#[derive(Clone, Debug, Default)]
+pub struct Skills {
+    uses: [u32; 3],
+}
+
+impl Skills {
+    pub fn level(&self, skill: Skill) -> u8 {
+        let uses = self.uses[skill as usize];
+        return SKILL_STEPS.iter().filter(|step| uses >= **step).count() as u8;
+    }
+
+    // counts one use, returns whether it raised the skill's level
+    pub fn practice(&mut self, skill: Skill) -> bool {
+        let before = self.level(skill);
+        self.uses[skill as usize] += 1;
+        return self.level(skill) > before;
+    }
+
+    pub fn save(&self, out: &mut Writer) {
+        for skill in Skill::ALL {
+            out.entry(&skill.to_string(), self.uses[skill as usize]);
+        }
+    }
+
+    // skills missing from the section start unused
+    pub fn load(section: &Section) -> Result<Skills, ContentError> {
+        let mut skills = Skills::default();
+        for skill in Skill::ALL {
+            let entry = section.require(&skill.to_string())?;
+            skills.uses[skill as usize] = entry.value.parse().map_err(|_| ContentError::InvalidValue(entry.line, entry.value.clone()))?;
+        }
+        return Ok(skills);
+    }
+}
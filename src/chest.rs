@@ -0,0 +1,107 @@
+use crate::content::{self, ContentError, Section};
+use crate::inventory::Inventory;
+use crate::item::Item;
+use crate::quest;
+use crate::world::World;
+
+// a container fixed to a tile, optionally locked with a key
+#[derive(Clone, Debug)]
+pub struct Chest {
+    pub id: String,
+    pub name: String,
+    pub pos: (usize, usize),
+    pub items: Inventory,
+    pub capacity: f32,
+    // catalogue id of the key that unlocks it
+    pub key: Option<String>,
+    pub locked: bool,
+    pub open: bool,
+}
+
+impl Chest {
+    // "chest" always refers to whichever chest is on the tile
+    pub fn matches(&self, name: &str) -> bool {
+        return name.eq_ignore_ascii_case("chest")
+            || self.id.eq_ignore_ascii_case(name)
+            || self.name.eq_ignore_ascii_case(name);
+    }
+}
+
+fn parse_chest(section: &Section, world: &World, errors: &mut Vec<ContentError>) -> Option<Chest> {
+    let name = section.require("name").map_err(|err| errors.push(err)).ok()?;
+    let tile = section.require("tile").map_err(|err| errors.push(err)).ok()?;
+    let args: Vec<&str> = tile.value.split_whitespace().collect();
+    let pos = quest::parse_coords(tile, &args, world).map_err(|err| errors.push(err)).ok()?;
+
+    let capacity = match section.get("capacity") {
+        Some(entry) => match entry.value.parse::<f32>() {
+            Ok(capacity) if capacity > 0.0 => capacity,
+            _ => {
+                errors.push(ContentError::InvalidValue(entry.line, entry.value.clone()));
+                return None;
+            },
+        },
+        None => 50.0,
+    };
+
+    let key = match section.get("key") {
+        Some(entry) => match Item::lookup(&entry.value) {
+            Some(item) => Some(item.id),
+            None => {
+                errors.push(ContentError::UnknownItem(entry.line, entry.value.clone()));
+                return None;
+            },
+        },
+        None => None,
+    };
+
+    let mut items = Inventory::default();
+    for entry in section.all("item") {
+        match Item::from_entry(entry) {
+            Ok(item) => items.add(item),
+            Err(err) => errors.push(err),
+        }
+    }
+
+    return Some(Chest {
+        id: section.id.clone(),
+        name: name.value.clone(),
+        pos,
+        items,
+        capacity,
+        locked: key.is_some(),
+        key,
+        open: false,
+    });
+}
+
+// loads every chest placed in the world
+pub fn load_chests(path: &str, world: &World) -> Result<Vec<Chest>, Vec<ContentError>> {
+    let sections = content::load(path).map_err(|err| vec!(err))?;
+    let mut errors = vec!();
+    let mut chests: Vec<Chest> = vec!();
+
+    for section in sections.iter() {
+        if section.kind != "chest" {
+            errors.push(ContentError::InvalidValue(section.line, section.kind.clone()));
+            continue;
+        }
+        if chests.iter().any(|c| c.id == section.id) {
+            errors.push(ContentError::Duplicate(section.line, section.id.clone()));
+            continue;
+        }
+        if let Some(chest) = parse_chest(section, world, &mut errors) {
+            // one chest per tile so "chest" is never ambiguous
+            if chests.iter().any(|c| c.pos == chest.pos) {
+                errors.push(ContentError::Duplicate(section.line, format!("{} {}", chest.pos.0, chest.pos.1)));
+                continue;
+            }
+            chests.push(chest);
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    return Ok(chests);
+}
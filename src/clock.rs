@@ -0,0 +1,137 @@
+use std::fmt::{self, Display, Formatter};
+
+// minutes that pass with every turn
+pub const MINUTES_PER_TURN: u64 = 10;
+pub const TURNS_PER_HOUR: u64 = 60 / MINUTES_PER_TURN;
+const MINUTES_PER_DAY: u64 = 24 * 60;
+// new games start in the morning
+const START_MINUTE: u64 = 8 * 60;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Phase {
+    Dawn,
+    Day,
+    Dusk,
+    Night,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 4] = [Phase::Dawn, Phase::Day, Phase::Dusk, Phase::Night];
+
+    pub fn parse(value: &str) -> Option<Phase> {
+        return Phase::ALL.into_iter().find(|p| p.to_string() == value);
+    }
+
+    // how many tiles away the player can make things out without a light
+    pub fn sight(&self) -> usize {
+        match self {
+            Phase::Day => return 2,
+            Phase::Dawn | Phase::Dusk => return 1,
+            Phase::Night => return 0,
+        }
+    }
+
+    // added to the description of wherever the player is, apart from in broad daylight
+    pub fn describe(&self) -> Option<&'static str> {
+        match self {
+            Phase::Dawn => return Some("The sky pales in the east."),
+            Phase::Day => return None,
+            Phase::Dusk => return Some("The sun sinks low and the shadows grow long."),
+            Phase::Night => return Some("It is dark, and the night is full of sounds."),
+        }
+    }
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use Phase::*;
+        match self {
+            Dawn => write!(f, "dawn"),
+            Day => write!(f, "day"),
+            Dusk => write!(f, "dusk"),
+            Night => write!(f, "night"),
+        }
+    }
+}
+
+fn minutes(turn: u64) -> u64 {
+    return START_MINUTE + turn * MINUTES_PER_TURN;
+}
+
+// counting from day 1
+pub fn day(turn: u64) -> u64 {
+    return minutes(turn) / MINUTES_PER_DAY + 1;
+}
+
+pub fn hour(turn: u64) -> u64 {
+    return minutes(turn) % MINUTES_PER_DAY / 60;
+}
+
+pub fn phase(turn: u64) -> Phase {
+    match hour(turn) {
+        5..=6 => return Phase::Dawn,
+        7..=18 => return Phase::Day,
+        19..=20 => return Phase::Dusk,
+        _ => return Phase::Night,
+    }
+}
+
+// "Day 2, 14:30 (day)"
+pub fn describe(turn: u64) -> String {
+    let minute = minutes(turn) % 60;
+    return format!("Day {}, {:02}:{:02} ({})", day(turn), hour(turn), minute, phase(turn));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // turn at which it is `hour`:00 on day 1, or the next day for hours before the start
+    fn at(hour: u64) -> u64 {
+        let start = START_MINUTE / 60;
+        return (hour + 24 - start) % 24 * TURNS_PER_HOUR;
+    }
+
+    #[test]
+    fn games_start_on_a_morning() {
+        assert_eq!(describe(0), "Day 1, 08:00 (day)");
+        assert_eq!(describe(1), "Day 1, 08:10 (day)");
+        assert_eq!(describe(TURNS_PER_HOUR * 6 + 3), "Day 1, 14:30 (day)");
+    }
+
+    #[test]
+    fn days_roll_over_at_midnight() {
+        assert_eq!(day(at(23) + TURNS_PER_HOUR - 1), 1);
+        assert_eq!(day(at(23) + TURNS_PER_HOUR), 2);
+        assert_eq!(describe(at(23) + TURNS_PER_HOUR), "Day 2, 00:00 (night)");
+        assert_eq!(day(24 * TURNS_PER_HOUR), 2);
+    }
+
+    #[test]
+    fn phases_follow_the_hour() {
+        assert_eq!(phase(at(4)), Phase::Night);
+        assert_eq!(phase(at(5)), Phase::Dawn);
+        assert_eq!(phase(at(6) + TURNS_PER_HOUR - 1), Phase::Dawn);
+        assert_eq!(phase(at(7)), Phase::Day);
+        assert_eq!(phase(at(18)), Phase::Day);
+        assert_eq!(phase(at(19)), Phase::Dusk);
+        assert_eq!(phase(at(21)), Phase::Night);
+        // the cycle repeats the next day
+        assert_eq!(phase(at(19) + 24 * TURNS_PER_HOUR), Phase::Dusk);
+    }
+
+    #[test]
+    fn sight_shrinks_after_dark() {
+        assert!(Phase::Day.sight() > Phase::Dusk.sight());
+        assert_eq!(Phase::Dawn.sight(), Phase::Dusk.sight());
+        assert_eq!(Phase::Night.sight(), 0);
+    }
+
+    #[test]
+    fn phases_parse_their_own_names() {
+        for phase in Phase::ALL {
+            assert_eq!(Phase::parse(&phase.to_string()), Some(phase));
+        }
+        assert_eq!(Phase::parse("noon"), None);
+    }
+}
@@ -0,0 +1,226 @@
+use crate::bestiary::{CreatureDef, Difficulty};
+use crate::rng::Rng;
+
+// roll on a d20 an attack against the player has to reach
+const PLAYER_EVASION: i32 = 8;
+// extra evasion while defending, which also halves the damage taken
+const DEFEND_BONUS: i32 = 4;
+// roll on a d20 needed to get away
+const FLEE_ROLL: i32 = 8;
+
+#[derive(Clone, Debug)]
+pub struct Creature {
+    pub name: String,
+    pub plural: String,
+    pub health: i32,
+    pub max_health: i32,
+    pub attack: u8,
+    pub armor: u8,
+    // roll on a d20 the player has to reach to hit it
+    pub evasion: i32,
+    // catalogue id and percent chance of it being dropped
+    pub loot: Vec<(String, i32)>,
+    pub gold: u32,
+    pub xp: u32,
+    pub poison: Option<(i32, u32)>,
+}
+
+impl Creature {
+    // a fresh creature from the bestiary, scaled to the difficulty
+    pub fn spawn(def: &CreatureDef, difficulty: &Difficulty, rng: &mut Rng) -> Creature {
+        let health = (def.health * difficulty.health / 100).max(1);
+        return Creature {
+            name: def.name.clone(),
+            plural: def.plural.clone(),
+            health,
+            max_health: health,
+            attack: (def.attack as i32 * difficulty.attack / 100).clamp(1, u8::MAX as i32) as u8,
+            armor: def.armor,
+            evasion: def.evasion,
+            loot: def.loot.clone(),
+            gold: rng.range(def.gold.0, def.gold.1) as u32,
+            xp: def.xp,
+            poison: def.poison,
+        };
+    }
+}
+
+// damage left after armor, which soaks up a growing share of each blow
+fn reduce(damage: i32, armor: u8) -> i32 {
+    return (damage * 10 / (10 + armor as i32)).max(1);
+}
+
+#[derive(Debug)]
+pub struct Combat {
+    pub creature: Creature,
+    // id of the roaming entity being fought, if it wasn't a chance encounter
+    pub entity: Option<String>,
+    pub defending: bool,
+    // every line printed during the fight
+    pub log: Vec<String>,
+}
+
+impl Combat {
+    pub fn new(creature: Creature, entity: Option<String>) -> Combat {
+        return Combat {
+            creature,
+            entity,
+            defending: false,
+            log: vec!(),
+        };
+    }
+
+    pub fn record(&mut self, line: String) {
+        println!("{}", line);
+        self.log.push(line);
+    }
+
+    // rough description of the creature's health
+    pub fn condition(&self) -> &str {
+        let creature = &self.creature;
+        if creature.health * 3 > creature.max_health * 2 {
+            return "barely scratched";
+        }
+        if creature.health * 3 > creature.max_health {
+            return "wounded";
+        }
+        return "badly hurt";
+    }
+
+    pub fn is_won(&self) -> bool {
+        return self.creature.health <= 0;
+    }
+
+    // the player's swing, a natural 20 deals double damage
+    pub fn player_attack(&mut self, attack: u8, rng: &mut Rng) {
+        let name = self.creature.name.to_lowercase();
+        let roll = rng.range(1, 20);
+        if roll < self.creature.evasion {
+            self.record(format!("You swing at the {} and miss.", name));
+            return;
+        }
+
+        let mut damage = reduce(rng.range(2, 5) + attack as i32, self.creature.armor);
+        if roll == 20 {
+            damage *= 2;
+            self.record("A critical hit!".to_string());
+        }
+        self.creature.health -= damage;
+        self.record(format!("You hit the {} for {} damage.", name, damage));
+    }
+
+    // the creature's turn, `dodge` is added to the player's evasion, returns the damage dealt to the player
+    pub fn creature_attack(&mut self, armor: u8, dodge: i32, rng: &mut Rng) -> u8 {
+        let name = self.creature.name.to_lowercase();
+        let defending = self.defending;
+        self.defending = false;
+
+        let evasion = PLAYER_EVASION + dodge + if defending { DEFEND_BONUS } else { 0 };
+        if rng.range(1, 20) < evasion {
+            self.record(format!("The {} attacks but misses.", name));
+            return 0;
+        }
+
+        let mut damage = reduce(rng.range(1, self.creature.attack as i32), armor);
+        if defending {
+            damage = (damage / 2).max(1);
+        }
+        self.record(format!("The {} hits you for {} damage.", name, damage));
+        return damage as u8;
+    }
+
+    pub fn try_flee(&self, dodge: i32, rng: &mut Rng) -> bool {
+        return rng.range(1, 20) + dodge >= FLEE_ROLL;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wolf() -> Creature {
+        return Creature {
+            name: "Wolf".to_string(),
+            plural: "wolves".to_string(),
+            health: 40,
+            max_health: 40,
+            attack: 12,
+            armor: 0,
+            evasion: 10,
+            loot: vec!(),
+            gold: 0,
+            xp: 10,
+            poison: None,
+        };
+    }
+
+    // a seeded generator whose first d20 comes up as `roll`
+    fn rolling(roll: i32) -> Rng {
+        return (1..).map(Rng::new).find(|rng| rng.clone().range(1, 20) == roll).unwrap();
+    }
+
+    #[test]
+    fn armor_soaks_up_damage() {
+        assert_eq!(reduce(10, 0), 10);
+        assert_eq!(reduce(10, 10), 5);
+        // every blow that lands does something
+        assert_eq!(reduce(1, 50), 1);
+    }
+
+    #[test]
+    fn player_misses_below_evasion() {
+        let mut combat = Combat::new(wolf(), None);
+        combat.player_attack(3, &mut rolling(9));
+        assert_eq!(combat.creature.health, 40);
+        assert_eq!(combat.log, vec!("You swing at the wolf and miss.".to_string()));
+    }
+
+    #[test]
+    fn player_hits_at_evasion() {
+        let mut combat = Combat::new(wolf(), None);
+        combat.player_attack(3, &mut rolling(10));
+        let damage = 40 - combat.creature.health;
+        assert!((5..=8).contains(&damage), "{}", damage);
+        assert_eq!(combat.log, vec!(format!("You hit the wolf for {} damage.", damage)));
+    }
+
+    #[test]
+    fn natural_twenty_doubles_damage() {
+        let mut combat = Combat::new(wolf(), None);
+        combat.player_attack(3, &mut rolling(20));
+        let damage = 40 - combat.creature.health;
+        assert!(damage % 2 == 0 && (10..=16).contains(&damage), "{}", damage);
+        assert_eq!(combat.log[0], "A critical hit!");
+    }
+
+    #[test]
+    fn defending_halves_damage_for_one_turn() {
+        let rng = rolling(15);
+        let mut combat = Combat::new(wolf(), None);
+        let full = combat.creature_attack(0, 0, &mut rng.clone());
+
+        combat.defending = true;
+        let halved = combat.creature_attack(0, 0, &mut rng.clone());
+        assert!(full > 0);
+        assert_eq!(halved, (full / 2).max(1));
+        assert!(!combat.defending);
+    }
+
+    #[test]
+    fn defending_and_dodging_raise_evasion() {
+        let mut combat = Combat::new(wolf(), None);
+        assert!(combat.creature_attack(0, 0, &mut rolling(10)) > 0);
+        assert_eq!(combat.creature_attack(0, 3, &mut rolling(10)), 0);
+
+        combat.defending = true;
+        assert_eq!(combat.creature_attack(0, 0, &mut rolling(11)), 0);
+    }
+
+    #[test]
+    fn fleeing_needs_the_roll() {
+        let combat = Combat::new(wolf(), None);
+        assert!(!combat.try_flee(0, &mut rolling(7)));
+        assert!(combat.try_flee(1, &mut rolling(7)));
+        assert!(combat.try_flee(0, &mut rolling(8)));
+    }
+}
@@ -0,0 +1,208 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+
+// Content files are plain text made of sections:
+//
+//   # comment
+//   [kind id]
+//   key = value
+//
+// Keys may repeat, and the order of entries within a section is kept.
+
+#[derive(Debug, PartialEq)]
+pub enum ContentError {
+    Unreadable(String),
+    Syntax(usize),
+    MissingKey(usize, String),
+    InvalidValue(usize, String),
+    Duplicate(usize, String),
+    UnknownItem(usize, String),
+    UnknownTown(usize, String),
+    UnknownTile(usize, String),
+    UnknownQuest(usize, String),
+    UnknownNpc(usize, String),
+    UnknownCreature(usize, String),
+    // a quest that can only be started once it is already completed
+    RequiresItself(usize, String),
+    // the map and the list of towns disagree
+    UnnamedTown(usize, usize),
+    MisplacedTown(String),
+}
+
+impl Error for ContentError {}
+
+impl Display for ContentError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use ContentError::*;
+        match self {
+            Unreadable(path) => write!(f, "could not read {}", path),
+            Syntax(line) => write!(f, "line {}: expected `[kind id]` or `key = value`", line),
+            MissingKey(line, key) => write!(f, "line {}: missing `{}`", line, key),
+            InvalidValue(line, value) => write!(f, "line {}: invalid value `{}`", line, value),
+            Duplicate(line, id) => write!(f, "line {}: `{}` is defined twice", line, id),
+            UnknownItem(line, name) => write!(f, "line {}: unknown item `{}`", line, name),
+            UnknownTown(line, name) => write!(f, "line {}: unknown town `{}`", line, name),
+            UnknownTile(line, name) => write!(f, "line {}: unknown tile `{}`", line, name),
+            UnknownQuest(line, id) => write!(f, "line {}: unknown quest `{}`", line, id),
+            UnknownNpc(line, name) => write!(f, "line {}: unknown npc `{}`", line, name),
+            UnknownCreature(line, name) => write!(f, "line {}: unknown creature `{}`", line, name),
+            RequiresItself(line, id) => write!(f, "line {}: quest `{}` requires itself", line, id),
+            UnnamedTown(x, y) => write!(f, "town at {}, {} has no name", x, y),
+            MisplacedTown(name) => write!(f, "`{}` is not on a town tile", name),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Entry {
+    pub line: usize,
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug)]
+pub struct Section {
+    pub kind: String,
+    pub id: String,
+    pub line: usize,
+    pub entries: Vec<Entry>,
+}
+
+impl Section {
+    pub fn get(&self, key: &str) -> Option<&Entry> {
+        return self.entries.iter().find(|e| e.key == key);
+    }
+
+    pub fn require(&self, key: &str) -> Result<&Entry, ContentError> {
+        match self.get(key) {
+            Some(entry) => return Ok(entry),
+            None => return Err(ContentError::MissingKey(self.line, key.to_string())),
+        }
+    }
+
+    pub fn all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a Entry> {
+        return self.entries.iter().filter(move |e| e.key == key);
+    }
+}
+
+pub fn parse(src: &str) -> Result<Vec<Section>, ContentError> {
+    let mut sections: Vec<Section> = vec!();
+
+    for (i, line) in src.lines().enumerate() {
+        let line_no = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            let mut header = line[1..line.len() - 1].split_whitespace();
+            let (kind, id) = match (header.next(), header.next(), header.next()) {
+                (Some(kind), Some(id), None) => (kind, id),
+                _ => return Err(ContentError::Syntax(line_no)),
+            };
+            sections.push(Section {
+                kind: kind.to_string(),
+                id: id.to_string(),
+                line: line_no,
+                entries: vec!(),
+            });
+            continue;
+        }
+
+        let (key, value) = match line.split_once('=') {
+            Some(pair) => pair,
+            None => return Err(ContentError::Syntax(line_no)),
+        };
+
+        match sections.last_mut() {
+            Some(section) => section.entries.push(Entry {
+                line: line_no,
+                key: key.trim().to_string(),
+                value: value.trim().to_string(),
+            }),
+            None => return Err(ContentError::Syntax(line_no)),
+        }
+    }
+
+    return Ok(sections);
+}
+
+// builds text in the same format `parse` reads, used for save files
+#[derive(Default)]
+pub struct Writer {
+    text: String,
+}
+
+impl Writer {
+    pub fn section(&mut self, kind: &str, id: impl Display) {
+        self.text.push_str(&format!("\n[{} {}]\n", kind, id));
+    }
+
+    pub fn entry(&mut self, key: &str, value: impl Display) {
+        self.text.push_str(&format!("{} = {}\n", key, value));
+    }
+
+    pub fn finish(self) -> String {
+        return self.text;
+    }
+}
+
+pub fn load(path: &str) -> Result<Vec<Section>, ContentError> {
+    match fs::read_to_string(path) {
+        Ok(src) => return parse(&src),
+        Err(_) => return Err(ContentError::Unreadable(path.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sections_and_entries_in_order() {
+        let sections = parse("# comment\n[item rope]\nname = Rope\n\ntag = a\ntag = b\n[item bread]\n").unwrap();
+        assert_eq!(sections.len(), 2);
+
+        let rope = &sections[0];
+        assert_eq!((rope.kind.as_str(), rope.id.as_str(), rope.line), ("item", "rope", 2));
+        assert_eq!(rope.require("name").unwrap().value, "Rope");
+        assert_eq!(rope.all("tag").map(|e| e.value.as_str()).collect::<Vec<_>>(), vec!("a", "b"));
+        assert_eq!(rope.require("weight").unwrap_err(), ContentError::MissingKey(2, "weight".to_string()));
+        assert!(sections[1].entries.is_empty());
+    }
+
+    #[test]
+    fn trims_keys_and_values_and_keeps_later_equals_signs() {
+        let sections = parse("[choice a]\n  text =  Yes -> b = c  \n").unwrap();
+        let entry = sections[0].get("text").unwrap();
+        assert_eq!((entry.line, entry.value.as_str()), (2, "Yes -> b = c"));
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        assert_eq!(parse("name = orphan").unwrap_err(), ContentError::Syntax(1));
+        assert_eq!(parse("[item]").unwrap_err(), ContentError::Syntax(1));
+        assert_eq!(parse("[item rope extra]").unwrap_err(), ContentError::Syntax(1));
+        assert_eq!(parse("[item rope]\nno equals here").unwrap_err(), ContentError::Syntax(2));
+    }
+
+    #[test]
+    fn writer_output_parses_back() {
+        let mut out = Writer::default();
+        out.section("player", "save");
+        out.entry("name", "Ana");
+        out.entry("gold", 12);
+        let sections = parse(&out.finish()).unwrap();
+
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].require("name").unwrap().value, "Ana");
+        assert_eq!(sections[0].require("gold").unwrap().value, "12");
+    }
+
+    #[test]
+    fn missing_file_is_unreadable() {
+        assert_eq!(load("./no/such/file.txt").unwrap_err(), ContentError::Unreadable("./no/such/file.txt".to_string()));
+    }
+}
@@ -0,0 +1,236 @@
+use std::fmt::{self, Display, Formatter};
+use std::sync::OnceLock;
+
+use crate::content::{self, ContentError, Entry, Section};
+use crate::inventory::Inventory;
+use crate::item::{self, Item};
+use crate::town::Service;
+use crate::world::TileName;
+
+// every recipe, loaded once at startup by `load_recipes`
+static RECIPES: OnceLock<Vec<Recipe>> = OnceLock::new();
+
+// where a recipe has to be made
+#[derive(Clone, Debug, PartialEq)]
+pub enum Station {
+    // a service of the town the player is in
+    Service(Service),
+    // the kind of tile the player is standing on
+    Terrain(TileName),
+    // a campfire the player has built
+    Campfire,
+}
+
+impl Display for Station {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Station::Service(service) => write!(f, "a {}", service.to_string().to_lowercase()),
+            Station::Terrain(tile) => write!(f, "the {}", tile.label()),
+            Station::Campfire => write!(f, "a campfire"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Recipe {
+    pub id: String,
+    // catalogue id and how many one craft makes
+    pub output: (String, i32),
+    // consumed when crafting
+    pub inputs: Vec<(String, i32)>,
+    // must be carried but are kept
+    pub tools: Vec<String>,
+    pub station: Option<Station>,
+}
+
+impl Recipe {
+    pub fn name(&self) -> &'static str {
+        return item::name_of(&self.output.0);
+    }
+
+    // e.g. "Wood x2, Bandage"
+    pub fn describe_inputs(&self) -> String {
+        return self.inputs.iter()
+            .map(|(id, count)| if *count > 1 { format!("{} x{}", item::name_of(id), count) } else { item::name_of(id).to_string() })
+            .collect::<Vec<_>>()
+            .join(", ");
+    }
+
+    // whether the inventory holds every input
+    pub fn has_materials(&self, inventory: &Inventory) -> bool {
+        return self.inputs.iter().all(|(id, count)| inventory.count(id) >= *count);
+    }
+
+    // takes the inputs out of the inventory, check `has_materials` first
+    pub fn use_materials(&self, inventory: &mut Inventory) {
+        for (id, count) in self.inputs.iter() {
+            inventory.remove(id, *count);
+        }
+    }
+}
+
+fn parse_station(entry: &Entry) -> Result<Station, ContentError> {
+    if entry.value == "campfire" {
+        return Ok(Station::Campfire);
+    }
+    if let Some(service) = Service::parse(&entry.value) {
+        return Ok(Station::Service(service));
+    }
+    match TileName::parse(&entry.value) {
+        Some(tile) => return Ok(Station::Terrain(tile)),
+        None => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+    }
+}
+
+fn parse_recipe(section: &Section, errors: &mut Vec<ContentError>) -> Option<Recipe> {
+    let output = section.require("output")
+        .and_then(Item::from_entry)
+        .map(|item| (item.id, item.count))
+        .map_err(|err| errors.push(err))
+        .ok()?;
+
+    let mut inputs = vec!();
+    for entry in section.all("input") {
+        match Item::from_entry(entry) {
+            Ok(item) => inputs.push((item.id, item.count)),
+            Err(err) => errors.push(err),
+        }
+    }
+    if section.get("input").is_none() {
+        errors.push(ContentError::MissingKey(section.line, "input".to_string()));
+    }
+
+    let mut tools = vec!();
+    for entry in section.all("tool") {
+        match Item::lookup(&entry.value) {
+            Some(item) => tools.push(item.id),
+            None => errors.push(ContentError::UnknownItem(entry.line, entry.value.clone())),
+        }
+    }
+
+    let station = match section.get("at").map(parse_station) {
+        Some(Ok(station)) => Some(station),
+        Some(Err(err)) => {
+            errors.push(err);
+            None
+        },
+        None => None,
+    };
+
+    return Some(Recipe {
+        id: section.id.clone(),
+        output,
+        inputs,
+        tools,
+        station,
+    });
+}
+
+// loads every recipe, must run after the item catalogue
+pub fn load_recipes(path: &str) -> Result<(), Vec<ContentError>> {
+    let sections = content::load(path).map_err(|err| vec!(err))?;
+    let mut errors = vec!();
+    let mut recipes: Vec<Recipe> = vec!();
+
+    for section in sections.iter() {
+        if section.kind != "recipe" {
+            errors.push(ContentError::InvalidValue(section.line, section.kind.clone()));
+            continue;
+        }
+        if recipes.iter().any(|r| r.id == section.id) {
+            errors.push(ContentError::Duplicate(section.line, section.id.clone()));
+            continue;
+        }
+        if let Some(recipe) = parse_recipe(section, &mut errors) {
+            recipes.push(recipe);
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    RECIPES.set(recipes).expect("recipes loaded twice");
+    return Ok(());
+}
+
+pub fn recipes() -> &'static [Recipe] {
+    return RECIPES.get().expect("recipes not loaded");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_one(text: &str) -> (Option<Recipe>, Vec<ContentError>) {
+        item::load_test_catalogue();
+        let sections = content::parse(text).unwrap();
+        let mut errors = vec!();
+        let recipe = parse_recipe(&sections[0], &mut errors);
+        return (recipe, errors);
+    }
+
+    fn stack(id: &str, count: i32) -> Item {
+        let mut item = Item::new(id).unwrap();
+        item.count = count;
+        return item;
+    }
+
+    #[test]
+    fn parses_counts_tools_and_station() {
+        let (recipe, errors) = parse_one("[recipe dagger]\noutput = dagger\ninput = iron_ore 2\ninput = wood\ntool = hammer\nat = forge\n");
+        assert!(errors.is_empty());
+        let recipe = recipe.unwrap();
+        assert_eq!(recipe.output, ("dagger".to_string(), 1));
+        assert_eq!(recipe.inputs, vec!(("iron_ore".to_string(), 2), ("wood".to_string(), 1)));
+        assert_eq!(recipe.tools, vec!("hammer".to_string()));
+        assert_eq!(recipe.station, Some(Station::Service(Service::Forge)));
+    }
+
+    #[test]
+    fn reports_bad_recipes() {
+        let (_, errors) = parse_one("[recipe dagger]\noutput = dagger\n");
+        assert!(matches!(errors[..], [ContentError::MissingKey(1, _)]));
+
+        let (_, errors) = parse_one("[recipe dagger]\noutput = dagger\ninput = mithril\n");
+        assert!(matches!(errors[..], [ContentError::UnknownItem(3, _)]));
+
+        let (_, errors) = parse_one("[recipe dagger]\noutput = dagger\ninput = wood 0\n");
+        assert!(matches!(errors[..], [ContentError::InvalidValue(3, _)]));
+
+        let (_, errors) = parse_one("[recipe dagger]\noutput = dagger\ninput = wood\nat = moon\n");
+        assert!(matches!(errors[..], [ContentError::InvalidValue(4, _)]));
+    }
+
+    #[test]
+    fn shipped_recipes_parse() {
+        item::load_test_catalogue();
+        let sections = content::load("./src/data/recipes.txt").unwrap();
+        let mut errors = vec!();
+        for section in sections.iter() {
+            assert!(parse_recipe(section, &mut errors).is_some());
+        }
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn describes_inputs() {
+        let (recipe, _) = parse_one("[recipe dagger]\noutput = dagger\ninput = iron_ore 2\ninput = wood\n");
+        assert_eq!(recipe.unwrap().describe_inputs(), "Iron Ore x2, Wood");
+    }
+
+    #[test]
+    fn checks_and_uses_materials() {
+        let (recipe, _) = parse_one("[recipe dagger]\noutput = dagger\ninput = iron_ore 2\ninput = wood\n");
+        let recipe = recipe.unwrap();
+
+        let mut inventory = Inventory::new(vec!(stack("iron_ore", 1), stack("wood", 2)));
+        assert!(!recipe.has_materials(&inventory));
+
+        inventory.add(stack("iron_ore", 2));
+        assert!(recipe.has_materials(&inventory));
+        recipe.use_materials(&mut inventory);
+        assert_eq!(inventory.count("iron_ore"), 1);
+        assert_eq!(inventory.count("wood"), 1);
+        assert!(!recipe.has_materials(&inventory));
+    }
+}
@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+
+use crate::content::{self, ContentError, Entry, Section};
+use crate::item::Item;
+use crate::inventory::Inventory;
+use crate::quest::Journal;
+use crate::status::Status;
+use crate::town::Town;
+use crate::world::World;
+
+// where an npc can be found
+#[derive(Clone, Debug, PartialEq)]
+pub enum Location {
+    Town(String),
+    Tile(usize, usize),
+}
+
+#[derive(Clone, Debug)]
+pub struct Npc {
+    pub id: String,
+    pub name: String,
+    pub location: Location,
+    // node the conversation starts at
+    pub greeting: String,
+}
+
+#[derive(Clone, Debug)]
+pub enum Condition {
+    HasItem(String, i32),
+    QuestActive(String),
+    QuestCompleted(String),
+    QuestNew(String),
+    Reputation(i32),
+}
+
+impl Condition {
+    pub fn holds(&self, inventory: &Inventory, journal: &Journal, reputation: i32) -> bool {
+        use Condition::*;
+        match self {
+            HasItem(item, count) => inventory.count(item) >= *count,
+            QuestActive(id) => journal.is_active(id),
+            QuestCompleted(id) => journal.is_completed(id),
+            QuestNew(id) => !journal.is_active(id) && !journal.is_completed(id),
+            Reputation(min) => reputation >= *min,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Effect {
+    GiveItem(String),
+    TakeItem(String),
+    Gold(i32),
+    Reputation(i32),
+    StartQuest(String),
+    Status(Status, u32),
+    // the npc says what the weather will do
+    Forecast,
+}
+
+#[derive(Clone, Debug)]
+pub struct Choice {
+    pub text: String,
+    // `None` ends the conversation
+    pub next: Option<String>,
+    pub conditions: Vec<Condition>,
+}
+
+// every dialogue node by id
+pub type Dialogue = HashMap<String, Node>;
+
+#[derive(Clone, Debug)]
+pub struct Node {
+    pub text: String,
+    pub effects: Vec<Effect>,
+    pub choices: Vec<Choice>,
+}
+
+impl Node {
+    // the choices currently on offer, in the order they are numbered
+    pub fn available(&self, inventory: &Inventory, journal: &Journal, reputation: i32) -> Vec<&Choice> {
+        return self.choices.iter()
+            .filter(|c| c.conditions.iter().all(|cond| cond.holds(inventory, journal, reputation)))
+            .collect();
+    }
+}
+
+fn parse_condition(entry: &Entry) -> Result<Condition, ContentError> {
+    let args: Vec<&str> = entry.value.split_whitespace().collect();
+    match args.as_slice() {
+        ["item", item, count] => {
+            let item = match Item::lookup(item) {
+                Some(item) => item.id,
+                None => return Err(ContentError::UnknownItem(entry.line, item.to_string())),
+            };
+            match count.parse() {
+                Ok(count) => return Ok(Condition::HasItem(item, count)),
+                Err(_) => return Err(ContentError::InvalidValue(entry.line, count.to_string())),
+            }
+        },
+        ["quest", id, "active"] => return Ok(Condition::QuestActive(id.to_string())),
+        ["quest", id, "completed"] => return Ok(Condition::QuestCompleted(id.to_string())),
+        ["quest", id, "new"] => return Ok(Condition::QuestNew(id.to_string())),
+        ["reputation", min] => match min.parse() {
+            Ok(min) => return Ok(Condition::Reputation(min)),
+            Err(_) => return Err(ContentError::InvalidValue(entry.line, min.to_string())),
+        },
+        _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+    }
+}
+
+fn parse_effect(entry: &Entry) -> Result<Effect, ContentError> {
+    let args: Vec<&str> = entry.value.split_whitespace().collect();
+    let item = |name: &str| match Item::lookup(name) {
+        Some(item) => Ok(item.id),
+        None => Err(ContentError::UnknownItem(entry.line, name.to_string())),
+    };
+    let amount = |value: &str| value.parse::<i32>()
+        .map_err(|_| ContentError::InvalidValue(entry.line, value.to_string()));
+
+    match args.as_slice() {
+        ["give", name] => return Ok(Effect::GiveItem(item(name)?)),
+        ["take", name] => return Ok(Effect::TakeItem(item(name)?)),
+        ["gold", value] => return Ok(Effect::Gold(amount(value)?)),
+        ["reputation", value] => return Ok(Effect::Reputation(amount(value)?)),
+        ["quest", id] => return Ok(Effect::StartQuest(id.to_string())),
+        ["forecast"] => return Ok(Effect::Forecast),
+        ["status", id, turns] => match (Status::parse(id), turns.parse()) {
+            (Some(status), Ok(turns)) if turns > 0 => return Ok(Effect::Status(status, turns)),
+            _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+        },
+        _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+    }
+}
+
+fn parse_node(section: &Section, errors: &mut Vec<ContentError>) -> Node {
+    let mut node = Node {
+        text: String::new(),
+        effects: vec!(),
+        choices: vec!(),
+    };
+
+    for entry in section.entries.iter() {
+        let result = match entry.key.as_str() {
+            "text" => {
+                node.text = entry.value.clone();
+                Ok(())
+            },
+            "effect" => parse_effect(entry).map(|effect| node.effects.push(effect)),
+            "choice" => match entry.value.rsplit_once("->") {
+                Some((text, next)) => {
+                    let next = next.trim();
+                    node.choices.push(Choice {
+                        text: text.trim().to_string(),
+                        next: if next == "end" { None } else { Some(next.to_string()) },
+                        conditions: vec!(),
+                    });
+                    Ok(())
+                },
+                None => Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+            },
+            // conditions apply to the choice listed just above them
+            "if" => match node.choices.last_mut() {
+                Some(choice) => parse_condition(entry).map(|cond| choice.conditions.push(cond)),
+                None => Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+            },
+            _ => Err(ContentError::InvalidValue(entry.line, entry.key.clone())),
+        };
+
+        if let Err(err) = result {
+            errors.push(err);
+        }
+    }
+
+    if node.text.is_empty() {
+        errors.push(ContentError::MissingKey(section.line, "text".to_string()));
+    }
+    return node;
+}
+
+fn parse_npc(section: &Section, world: &World) -> Result<Npc, ContentError> {
+    let name = section.require("name")?;
+    let greeting = section.require("greeting")?;
+    let location = section.require("location")?;
+
+    let args: Vec<&str> = location.value.split_whitespace().collect();
+    let location = match args.split_first() {
+        Some((&"town", town)) => {
+            let town = town.join(" ");
+            if !Town::exists(&town) {
+                return Err(ContentError::UnknownTown(location.line, town));
+            }
+            Location::Town(town)
+        },
+        Some((&"tile", [x, y])) => match (x.parse::<i32>(), y.parse::<i32>()) {
+            (Ok(x), Ok(y)) if world.in_bounds(x, y) && world.is_accessible(x as usize, y as usize) => {
+                Location::Tile(x as usize, y as usize)
+            },
+            _ => return Err(ContentError::UnknownTile(location.line, format!("{} {}", x, y))),
+        },
+        _ => return Err(ContentError::InvalidValue(location.line, location.value.clone())),
+    };
+
+    return Ok(Npc {
+        id: section.id.clone(),
+        name: name.value.clone(),
+        location,
+        greeting: greeting.value.clone(),
+    });
+}
+
+// loads every npc and dialogue node in `path`; quest references are checked later by `check_quests`
+pub fn load_npcs(path: &str, world: &World) -> Result<(Vec<Npc>, Dialogue), Vec<ContentError>> {
+    let sections = content::load(path).map_err(|err| vec!(err))?;
+    let mut errors = vec!();
+    let mut npcs: Vec<Npc> = vec!();
+    let mut nodes = HashMap::new();
+
+    for section in sections.iter() {
+        match section.kind.as_str() {
+            "npc" => {
+                if npcs.iter().any(|npc| npc.id == section.id) {
+                    errors.push(ContentError::Duplicate(section.line, section.id.clone()));
+                }
+                match parse_npc(section, world) {
+                    Ok(npc) => npcs.push(npc),
+                    Err(err) => errors.push(err),
+                }
+            },
+            "node" => {
+                if nodes.contains_key(&section.id) {
+                    errors.push(ContentError::Duplicate(section.line, section.id.clone()));
+                }
+                nodes.insert(section.id.clone(), parse_node(section, &mut errors));
+            },
+            _ => errors.push(ContentError::InvalidValue(section.line, section.kind.clone())),
+        }
+    }
+
+    // every conversation must lead to nodes that exist
+    for section in sections.iter() {
+        let targets = match section.kind.as_str() {
+            "npc" => section.all("greeting").map(|e| (e.line, e.value.as_str())).collect::<Vec<_>>(),
+            _ => section.all("choice")
+                .filter_map(|e| e.value.rsplit_once("->").map(|(_, next)| (e.line, next.trim())))
+                .filter(|(_, next)| *next != "end")
+                .collect(),
+        };
+        for (line, target) in targets {
+            if !nodes.contains_key(target) {
+                errors.push(ContentError::InvalidValue(line, target.to_string()));
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    return Ok((npcs, nodes));
+}
+
+// reports dialogue that mentions quests which were never loaded
+pub fn check_quests(path: &str, known: impl Fn(&str) -> bool) -> Vec<ContentError> {
+    let sections = match content::load(path) {
+        Ok(sections) => sections,
+        Err(err) => return vec!(err),
+    };
+
+    let mut errors = vec!();
+    for section in sections.iter().filter(|s| s.kind == "node") {
+        for entry in section.entries.iter().filter(|e| e.key == "if" || e.key == "effect") {
+            let args: Vec<&str> = entry.value.split_whitespace().collect();
+            if let ["quest", id, ..] = args.as_slice() {
+                if !known(id) {
+                    errors.push(ContentError::UnknownQuest(entry.line, id.to_string()));
+                }
+            }
+        }
+    }
+    return errors;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs};
+    use crate::item;
+
+    // load_npcs reads from a file, so each test writes its own
+    fn load(name: &str, src: &str) -> Result<(Vec<Npc>, Dialogue), Vec<ContentError>> {
+        item::load_test_catalogue();
+        let path = env::temp_dir().join(format!("npcs-{}-{}.txt", name, std::process::id()));
+        fs::write(&path, src).unwrap();
+        let loaded = load_npcs(path.to_str().unwrap(), &World::test_map());
+        fs::remove_file(&path).unwrap();
+        return loaded;
+    }
+
+    #[test]
+    fn shipped_npcs_load() {
+        item::load_test_catalogue();
+        let (npcs, dialogue) = load_npcs("./src/data/npcs.txt", &World::test_map()).unwrap();
+        assert!(npcs.iter().all(|npc| dialogue.contains_key(&npc.greeting)));
+    }
+
+    #[test]
+    fn parses_choices_conditions_and_effects() {
+        let (npcs, dialogue) = load("choices", "[npc ada]\nname = Ada\ngreeting = hello\nlocation = town Emberfall\n\
+            [node hello]\ntext = Hi.\neffect = give bread\neffect = status blessed 10\nchoice = Trade? -> trade\n\
+            if = item rope 2\nif = quest errand new\nchoice = Bye. -> end\n[node trade]\ntext = Sure.\n").unwrap();
+        assert_eq!(npcs[0].location, Location::Town("Emberfall".to_string()));
+
+        let hello = &dialogue["hello"];
+        assert_eq!(hello.effects.len(), 2);
+        assert_eq!(hello.choices[0].next.as_deref(), Some("trade"));
+        assert_eq!(hello.choices[0].conditions.len(), 2);
+        assert_eq!(hello.choices[1].next, None);
+    }
+
+    #[test]
+    fn rejects_npcs_and_nodes_defined_twice() {
+        let errors = load("twice", "[npc ada]\nname = Ada\ngreeting = hello\nlocation = town Emberfall\n\
+            [npc ada]\nname = Ada\ngreeting = hello\nlocation = town Emberfall\n\
+            [node hello]\ntext = Hi.\n[node hello]\ntext = Hi.\n").unwrap_err();
+        assert_eq!(errors, vec!(ContentError::Duplicate(5, "ada".to_string()), ContentError::Duplicate(11, "hello".to_string())));
+    }
+
+    #[test]
+    fn reports_bad_lines_and_missing_nodes() {
+        let errors = load("bad", "[npc ada]\nname = Ada\ngreeting = nowhere\nlocation = town Atlantis\n\
+            [node hello]\neffect = status cursed 5\nif = reputation 3\nchoice = Go -> gone\n").unwrap_err();
+        assert!(errors.contains(&ContentError::UnknownTown(4, "Atlantis".to_string())));
+        assert!(errors.contains(&ContentError::InvalidValue(6, "status cursed 5".to_string())));
+        assert!(errors.contains(&ContentError::InvalidValue(7, "reputation 3".to_string())));
+        assert!(errors.contains(&ContentError::MissingKey(5, "text".to_string())));
+        assert!(errors.contains(&ContentError::InvalidValue(3, "nowhere".to_string())));
+        assert!(errors.contains(&ContentError::InvalidValue(8, "gone".to_string())));
+    }
+}
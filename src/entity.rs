@@ -0,0 +1,449 @@
+use crate::bestiary;
+use crate::content::{self, ContentError, Entry, Section, Writer};
+use crate::rng::Rng;
+use crate::town::{Town, TOWNS};
+use crate::world::{TileName, World};
+
+// turns before a defeated creature turns up at its home again
+const RESPAWN_TURNS: u64 = 200;
+// turns a creature leaves the player alone after they get away from it
+const CALM_TURNS: u64 = 10;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Behaviour {
+    // drifts around its home
+    Wander,
+    // walks its route from waypoint to waypoint
+    Patrol,
+    // heads for the player
+    Chase,
+    // keeps away from the player
+    Flee,
+}
+
+impl Behaviour {
+    pub fn parse(value: &str) -> Option<Behaviour> {
+        match value {
+            "wander" => Some(Behaviour::Wander),
+            "patrol" => Some(Behaviour::Patrol),
+            "chase" => Some(Behaviour::Chase),
+            "flee" => Some(Behaviour::Flee),
+            _ => None,
+        }
+    }
+}
+
+// something that moves around the map on its own, a creature or an npc
+#[derive(Clone, Debug)]
+pub struct Entity {
+    pub id: String,
+    pub name: String,
+    // bestiary id, creatures fight the player when they reach them
+    pub creature: Option<String>,
+    // npc that travels with the entity and can be talked to wherever it is
+    pub npc: Option<String>,
+    pub symbol: char,
+    pub home: (usize, usize),
+    pub pos: (usize, usize),
+    // what it does while the player is out of sight
+    pub behaviour: Behaviour,
+    // what it does once it sees the player
+    pub reaction: Option<Behaviour>,
+    pub sight: usize,
+    // how far a wanderer strays from home
+    pub roam: usize,
+    pub route: Vec<(usize, usize)>,
+    // index into `route` of where it is heading
+    pub waypoint: usize,
+    // turn until which it ignores the player
+    pub calm_until: u64,
+    // turn it comes back after being defeated
+    pub respawn: Option<u64>,
+}
+
+impl Entity {
+    pub fn is_hostile(&self) -> bool {
+        return self.creature.is_some();
+    }
+
+    // whether it is on the map at this turn
+    pub fn is_active(&self, turn: u64) -> bool {
+        return self.respawn.is_none_or(|respawn| turn >= respawn);
+    }
+
+    // "A wolf" or an npc's own name
+    pub fn describe(&self) -> String {
+        if self.npc.is_some() {
+            return self.name.clone();
+        }
+        let name = self.name.to_lowercase();
+        let article = if name.starts_with(['a', 'e', 'i', 'o', 'u']) { "An" } else { "A" };
+        return format!("{} {}", article, name);
+    }
+
+    pub fn defeat(&mut self, turn: u64) {
+        self.respawn = Some(turn + RESPAWN_TURNS);
+        self.pos = self.home;
+        self.waypoint = 0;
+    }
+
+    pub fn calm(&mut self, turn: u64) {
+        self.calm_until = turn + CALM_TURNS;
+    }
+
+    fn can_enter(&self, world: &World, x: usize, y: usize) -> bool {
+        if !world.in_bounds(x as i32, y as i32) || !world.is_accessible(x, y) {
+            return false;
+        }
+        // creatures keep out of towns
+        return !self.is_hostile() || world.tile_name(x, y) != TileName::Town;
+    }
+
+    fn neighbours(&self, world: &World) -> Vec<(usize, usize)> {
+        let (x, y) = self.pos;
+        return [(x, y - 1), (x + 1, y), (x, y + 1), (x - 1, y)].into_iter()
+            .filter(|(nx, ny)| self.can_enter(world, *nx, *ny))
+            .collect();
+    }
+
+    // what it does this turn given where the player is, `None` when the player is hidden in a town
+    fn current_behaviour(&self, player: Option<(usize, usize)>, turn: u64) -> Behaviour {
+        if let (Some(reaction), Some((px, py))) = (&self.reaction, player) {
+            let seen = self.pos.0.abs_diff(px).max(self.pos.1.abs_diff(py)) <= self.sight;
+            if seen && turn >= self.calm_until {
+                return reaction.clone();
+            }
+        }
+        return self.behaviour.clone();
+    }
+
+    // moves one tile according to its behaviour
+    pub fn step(&mut self, world: &World, player: Option<(usize, usize)>, rng: &mut Rng) {
+        let next = match self.current_behaviour(player, world.turn()) {
+            Behaviour::Wander => {
+                let options: Vec<(usize, usize)> = self.neighbours(world).into_iter()
+                    .filter(|(x, y)| x.abs_diff(self.home.0).max(y.abs_diff(self.home.1)) <= self.roam)
+                    .collect();
+                // stays put now and then
+                if options.is_empty() || rng.range(1, 3) == 1 {
+                    None
+                } else {
+                    Some(*rng.pick(&options))
+                }
+            },
+            Behaviour::Patrol => {
+                if self.route.is_empty() {
+                    return;
+                }
+                if self.pos == self.route[self.waypoint] {
+                    self.waypoint = (self.waypoint + 1) % self.route.len();
+                }
+                world.step_towards(self.pos, self.route[self.waypoint], |x, y| self.can_enter(world, x, y))
+            },
+            Behaviour::Chase => {
+                let target = player.unwrap();
+                world.step_towards(self.pos, target, |x, y| self.can_enter(world, x, y))
+            },
+            Behaviour::Flee => {
+                let (px, py) = player.unwrap();
+                let distance = |(x, y): (usize, usize)| x.abs_diff(px) + y.abs_diff(py);
+                self.neighbours(world).into_iter()
+                    .filter(|pos| distance(*pos) > distance(self.pos))
+                    .max_by_key(|pos| distance(*pos))
+            },
+        };
+
+        // paths end on the target itself, which may be somewhere it can't go
+        if let Some(pos) = next.filter(|(x, y)| self.can_enter(world, *x, *y)) {
+            self.pos = pos;
+        }
+    }
+
+    pub fn save(&self, out: &mut Writer) {
+        out.section("entity", &self.id);
+        out.entry("pos", format!("{} {}", self.pos.0, self.pos.1));
+        out.entry("waypoint", self.waypoint);
+        out.entry("calm_until", self.calm_until);
+        if let Some(respawn) = self.respawn {
+            out.entry("respawn", respawn);
+        }
+    }
+
+    pub fn load(&mut self, section: &Section, world: &World) -> Result<(), ContentError> {
+        let entry = section.require("pos")?;
+        self.pos = parse_tile(entry, world)?;
+
+        let entry = section.require("waypoint")?;
+        match entry.value.parse::<usize>() {
+            Ok(waypoint) if waypoint < self.route.len().max(1) => self.waypoint = waypoint,
+            _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+        }
+
+        let entry = section.require("calm_until")?;
+        self.calm_until = entry.value.parse().map_err(|_| ContentError::InvalidValue(entry.line, entry.value.clone()))?;
+
+        self.respawn = match section.get("respawn") {
+            Some(entry) => Some(entry.value.parse().map_err(|_| ContentError::InvalidValue(entry.line, entry.value.clone()))?),
+            None => None,
+        };
+        return Ok(());
+    }
+}
+
+// `<x> <y>` of a tile anything could stand on
+fn parse_tile(entry: &Entry, world: &World) -> Result<(usize, usize), ContentError> {
+    let args: Vec<&str> = entry.value.split_whitespace().collect();
+    match args.as_slice() {
+        [x, y] => match (x.parse::<i32>(), y.parse::<i32>()) {
+            (Ok(x), Ok(y)) if world.in_bounds(x, y) && world.is_accessible(x as usize, y as usize) => {
+                return Ok((x as usize, y as usize));
+            },
+            _ => return Err(ContentError::UnknownTile(entry.line, entry.value.clone())),
+        },
+        _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+    }
+}
+
+// a town name or `<x> <y>`
+fn parse_waypoint(entry: &Entry, world: &World) -> Result<(usize, usize), ContentError> {
+    if Town::exists(&entry.value) {
+        let (x, y, _, _) = TOWNS.iter().find(|t| t.2 == entry.value).unwrap();
+        return Ok((*x, *y));
+    }
+    return parse_tile(entry, world);
+}
+
+fn parse_behaviour(entry: &Entry) -> Result<Behaviour, ContentError> {
+    return Behaviour::parse(&entry.value).ok_or(ContentError::InvalidValue(entry.line, entry.value.clone()));
+}
+
+fn parse_entity(section: &Section, world: &World, errors: &mut Vec<ContentError>) -> Option<Entity> {
+    let error_count = errors.len();
+
+    let creature = match section.get("creature") {
+        Some(entry) => match bestiary::find(&entry.value) {
+            Some(creature) => Some(creature),
+            None => {
+                errors.push(ContentError::UnknownCreature(entry.line, entry.value.clone()));
+                None
+            },
+        },
+        None => None,
+    };
+    let npc = match section.get("npc") {
+        Some(entry) => match world.find_npc(&entry.value) {
+            Some(npc) => Some(npc),
+            None => {
+                errors.push(ContentError::UnknownNpc(entry.line, entry.value.clone()));
+                None
+            },
+        },
+        None => None,
+    };
+    let name = match (section.get("name"), npc, creature) {
+        (Some(entry), _, _) => entry.value.clone(),
+        (None, Some(npc), _) => npc.name.clone(),
+        (None, None, Some(creature)) => creature.name.clone(),
+        (None, None, None) => {
+            errors.push(ContentError::MissingKey(section.line, "name".to_string()));
+            return None;
+        },
+    };
+
+    let Some(first) = name.chars().next() else {
+        let line = section.get("name").map_or(section.line, |entry| entry.line);
+        errors.push(ContentError::InvalidValue(line, name));
+        return None;
+    };
+
+    let symbol = match section.get("symbol") {
+        Some(entry) if entry.value.chars().count() == 1 => entry.value.chars().next().unwrap(),
+        Some(entry) => {
+            errors.push(ContentError::InvalidValue(entry.line, entry.value.clone()));
+            return None;
+        },
+        None => first.to_ascii_lowercase(),
+    };
+
+    let home = section.require("tile").and_then(|entry| parse_tile(entry, world));
+    // chasing and fleeing need a player in sight, so they only make sense as a reaction
+    let behaviour = section.require("behaviour").and_then(|entry| match parse_behaviour(entry)? {
+        Behaviour::Chase | Behaviour::Flee => Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+        behaviour => Ok(behaviour),
+    });
+    let reaction = section.get("reaction").map(parse_behaviour).transpose();
+
+    let mut route = vec!();
+    for entry in section.all("waypoint") {
+        match parse_waypoint(entry, world) {
+            Ok(pos) => route.push(pos),
+            Err(err) => errors.push(err),
+        }
+    }
+
+    let mut number = |key: &str, default: usize| match section.get(key) {
+        Some(entry) => entry.value.parse().map_err(|_| errors.push(ContentError::InvalidValue(entry.line, entry.value.clone()))).unwrap_or(default),
+        None => default,
+    };
+    let sight = number("sight", 4);
+    let roam = number("roam", 5);
+
+    match (home, behaviour, reaction) {
+        (Ok(home), Ok(behaviour), Ok(reaction)) if errors.len() == error_count => {
+            if behaviour == Behaviour::Patrol && route.is_empty() {
+                errors.push(ContentError::MissingKey(section.line, "waypoint".to_string()));
+                return None;
+            }
+            return Some(Entity {
+                id: section.id.clone(),
+                name,
+                creature: creature.map(|c| c.id.clone()),
+                npc: npc.map(|n| n.id.clone()),
+                symbol,
+                home,
+                pos: home,
+                behaviour,
+                reaction,
+                sight,
+                roam,
+                route,
+                waypoint: 0,
+                calm_until: 0,
+                respawn: None,
+            });
+        },
+        (home, behaviour, reaction) => {
+            errors.extend(home.err());
+            errors.extend(behaviour.err());
+            errors.extend(reaction.err());
+            return None;
+        },
+    }
+}
+
+// loads everything that roams the map, must run after the bestiary and the npcs
+pub fn load_entities(path: &str, world: &World) -> Result<Vec<Entity>, Vec<ContentError>> {
+    let sections = content::load(path).map_err(|err| vec!(err))?;
+    let mut errors = vec!();
+    let mut entities: Vec<Entity> = vec!();
+
+    for section in sections.iter() {
+        if section.kind != "entity" {
+            errors.push(ContentError::InvalidValue(section.line, section.kind.clone()));
+            continue;
+        }
+        if entities.iter().any(|e| e.id == section.id) {
+            errors.push(ContentError::Duplicate(section.line, section.id.clone()));
+            continue;
+        }
+        if let Some(entity) = parse_entity(section, world, &mut errors) {
+            entities.push(entity);
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    return Ok(entities);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a wolf on the open plains west of Kingsreach
+    fn wolf(pos: (usize, usize)) -> Entity {
+        return Entity {
+            id: "wolf".to_string(),
+            name: "Wolf".to_string(),
+            creature: Some("wolf".to_string()),
+            npc: None,
+            symbol: 'w',
+            home: pos,
+            pos,
+            behaviour: Behaviour::Wander,
+            reaction: Some(Behaviour::Chase),
+            sight: 3,
+            roam: 2,
+            route: vec!(),
+            waypoint: 0,
+            calm_until: 0,
+            respawn: None,
+        };
+    }
+
+    #[test]
+    fn wanderers_stay_near_home() {
+        let world = World::test_map();
+        let mut rng = Rng::new(7);
+        let mut wolf = wolf((10, 19));
+        let mut moved = false;
+        for _ in 0..200 {
+            wolf.step(&world, None, &mut rng);
+            moved |= wolf.pos != wolf.home;
+            assert!(wolf.pos.0.abs_diff(10).max(wolf.pos.1.abs_diff(19)) <= 2, "{:?}", wolf.pos);
+        }
+        assert!(moved);
+    }
+
+    #[test]
+    fn patrols_cycle_through_the_route() {
+        let world = World::test_map();
+        let mut rng = Rng::new(7);
+        let mut guard = wolf((10, 19));
+        guard.behaviour = Behaviour::Patrol;
+        guard.route = vec!((10, 19), (12, 19));
+
+        let mut path = vec!();
+        for _ in 0..5 {
+            guard.step(&world, None, &mut rng);
+            path.push(guard.pos);
+        }
+        assert_eq!(path, vec!((11, 19), (12, 19), (11, 19), (10, 19), (11, 19)));
+    }
+
+    #[test]
+    fn reacts_only_to_a_player_in_sight() {
+        let wolf = wolf((10, 19));
+        assert_eq!(wolf.current_behaviour(Some((13, 19)), 0), Behaviour::Chase);
+        assert_eq!(wolf.current_behaviour(Some((14, 19)), 0), Behaviour::Wander);
+        // hidden in a town
+        assert_eq!(wolf.current_behaviour(None, 0), Behaviour::Wander);
+    }
+
+    #[test]
+    fn calmed_creatures_ignore_the_player_for_a_while() {
+        let mut wolf = wolf((10, 19));
+        wolf.calm(0);
+        assert_eq!(wolf.current_behaviour(Some((11, 19)), CALM_TURNS - 1), Behaviour::Wander);
+        assert_eq!(wolf.current_behaviour(Some((11, 19)), CALM_TURNS), Behaviour::Chase);
+    }
+
+    #[test]
+    fn chases_and_flees_the_player() {
+        let world = World::test_map();
+        let mut rng = Rng::new(7);
+        let mut deer = wolf((12, 19));
+        deer.reaction = Some(Behaviour::Flee);
+        let mut wolf = wolf((10, 19));
+        wolf.step(&world, Some((13, 19)), &mut rng);
+        assert_eq!(wolf.pos, (11, 19));
+
+        deer.step(&world, Some((10, 19)), &mut rng);
+        assert_eq!(deer.pos.0.abs_diff(10) + deer.pos.1.abs_diff(19), 3);
+    }
+
+    #[test]
+    fn creatures_keep_out_of_towns() {
+        let world = World::test_map();
+        let mut rng = Rng::new(7);
+        // Kingsreach is at 20 20
+        let mut wolf = wolf((19, 20));
+        assert!(!wolf.neighbours(&world).contains(&(20, 20)));
+        wolf.step(&world, Some((20, 20)), &mut rng);
+        assert_ne!(wolf.pos, (20, 20));
+
+        wolf.creature = None;
+        assert!(wolf.neighbours(&world).contains(&(20, 20)));
+    }
+}
@@ -0,0 +1,122 @@
+use crate::content::{ContentError, Section, Writer};
+use crate::item::{Item, ItemEffect, Slot};
+
+// gear the player is wearing or holding, one item per slot
+#[derive(Debug, Default)]
+pub struct Equipment {
+    // indexed by `Slot as usize`
+    slots: [Option<Item>; 5],
+}
+
+impl Equipment {
+    // puts a single item in its slot and hands back whatever was there before
+    pub fn equip(&mut self, item: Item) -> Option<Item> {
+        let slot = item.def().slot.expect("only gear can be equipped");
+        return self.slots[slot as usize].replace(item);
+    }
+
+    // takes off the item in the named slot, or the equipped item with that name
+    pub fn unequip(&mut self, name: &str) -> Option<Item> {
+        if let Some(slot) = Slot::parse(name) {
+            return self.slots[slot as usize].take();
+        }
+        let slot = self.slots.iter().position(|item| item.as_ref().is_some_and(|i| i.matches(name)))?;
+        return self.slots[slot].take();
+    }
+
+    pub fn save(&self, out: &mut Writer) {
+        for item in self.slots.iter().flatten() {
+            out.entry("equip", item.saved());
+        }
+    }
+
+    // reads the `equip` entries of a section
+    pub fn load(section: &Section) -> Result<Equipment, ContentError> {
+        let mut equipment = Equipment::default();
+        for entry in section.all("equip") {
+            let item = Item::from_saved(entry)?;
+            if item.def().slot.is_none() {
+                return Err(ContentError::InvalidValue(entry.line, entry.value.clone()));
+            }
+            equipment.equip(item);
+        }
+        return Ok(equipment);
+    }
+
+    pub fn get(&self, slot: Slot) -> Option<&Item> {
+        return self.slots[slot as usize].as_ref();
+    }
+
+    pub fn has_effect(&self, effect: ItemEffect) -> bool {
+        return self.slots.iter().flatten().any(|item| item.def().effect == Some(effect));
+    }
+
+    // wears down every held light by a turn, returns the ones that burned out
+    pub fn burn_lights(&mut self) -> Vec<Item> {
+        let mut burned = vec!();
+        for slot in self.slots.iter_mut() {
+            let Some(item) = slot else {
+                continue;
+            };
+            if item.def().effect != Some(ItemEffect::Light) {
+                continue;
+            }
+            if let Some(left) = item.durability {
+                item.durability = Some(left.saturating_sub(1));
+                if left <= 1 {
+                    burned.extend(slot.take());
+                }
+            }
+        }
+        return burned;
+    }
+
+    pub fn has(&self, id: &str) -> bool {
+        return self.slots.iter().flatten().any(|item| item.id == id);
+    }
+
+    pub fn armor(&self) -> u8 {
+        return self.slots.iter().flatten().map(|item| item.def().armor).sum();
+    }
+
+    pub fn attack(&self) -> u8 {
+        return self.slots.iter().flatten().map(|item| item.def().attack).sum();
+    }
+
+    pub fn weight(&self) -> f32 {
+        return self.slots.iter().flatten().map(|item| item.weight()).sum();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content;
+    use crate::item;
+
+    #[test]
+    fn round_trips_through_saves() {
+        item::load_test_catalogue();
+        let mut equipment = Equipment::default();
+        let mut torch = Item::new("torch").unwrap();
+        torch.durability = Some(7);
+        equipment.equip(torch);
+        equipment.equip(Item::new("dagger").unwrap());
+
+        let mut out = Writer::default();
+        out.section("player", "save");
+        equipment.save(&mut out);
+        let loaded = Equipment::load(&content::parse(&out.finish()).unwrap()[0]).unwrap();
+
+        assert_eq!(loaded.get(Slot::Offhand).and_then(|item| item.durability), Some(7));
+        assert!(loaded.get(Slot::Weapon).is_some_and(|item| item.id == "dagger"));
+        assert!(loaded.get(Slot::Head).is_none());
+    }
+
+    #[test]
+    fn only_gear_loads_into_slots() {
+        item::load_test_catalogue();
+        let sections = content::parse("[player save]\nequip = bread 1\n").unwrap();
+        assert_eq!(Equipment::load(&sections[0]).unwrap_err(), ContentError::InvalidValue(2, "bread 1".to_string()));
+    }
+}
@@ -1,12 +1,14 @@
 use crate::player::Player;
 use crate::world::World;
 
+#[allow(dead_code)] // not wired into the game loop yet
 #[derive(Debug)]
 pub(crate) struct GameState {
     world: Option<World>,
     player: Option<Player>,
 }
 
+#[allow(dead_code)]
 impl GameState {
     pub fn new() -> GameState {
         GameState { world: None, player: None }
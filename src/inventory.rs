@@ -0,0 +1,302 @@
+use std::io::stdout;
+use crossterm::{
+    execute,
+    cursor};
+
+use crate::content::{ContentError, Section, Writer};
+use crate::item::Item;
+
+#[derive(Clone, Debug, Default)]
+pub struct Inventory {
+    items: Vec<Item>,
+}
+
+impl Inventory {
+    pub fn new(items: Vec<Item>) -> Inventory {
+        let mut inventory = Inventory::default();
+        for item in items {
+            inventory.add(item);
+        }
+        return inventory;
+    }
+
+    // empties the inventory, handing back every stack
+    pub fn take_all(&mut self) -> Vec<Item> {
+        return std::mem::take(&mut self.items);
+    }
+
+    // tops up existing stacks of the same item before starting new ones
+    pub fn add(&mut self, mut item: Item) {
+        let limit = item.def().stack;
+        for stack in self.items.iter_mut() {
+            if item.count == 0 {
+                return;
+            }
+            // worn items never share a stack with fresh ones
+            if stack.id != item.id || stack.durability != item.durability || stack.count >= limit {
+                continue;
+            }
+            let moved = (limit - stack.count).min(item.count);
+            stack.count += moved;
+            item.count -= moved;
+        }
+
+        while item.count > limit {
+            item.count -= limit;
+            let mut stack = Item::new(&item.id).unwrap();
+            stack.count = limit;
+            stack.durability = item.durability;
+            self.items.push(stack);
+        }
+        // the rest goes in as it is, so containers keep their contents
+        if item.count > 0 {
+            self.items.push(item);
+        }
+    }
+
+    // index of the first stack matching an id or display name
+    pub fn find(&self, name: &str) -> Option<usize> {
+        return self.items.iter().position(|item| item.matches(name));
+    }
+
+    pub fn get(&self, index: usize) -> &Item {
+        return &self.items[index];
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> &mut Item {
+        return &mut self.items[index];
+    }
+
+    // splits up to `count` items off the stack at `index`
+    pub fn take(&mut self, index: usize, count: i32) -> Item {
+        let stack = &mut self.items[index];
+        if count >= stack.count {
+            return self.items.remove(index);
+        }
+
+        stack.count -= count;
+        let mut item = Item::new(&stack.id).unwrap();
+        item.count = count;
+        item.durability = stack.durability;
+        return item;
+    }
+
+    // pulls out the whole stack matching `name`, or every stack for "all"
+    pub fn take_named(&mut self, name: &str) -> Vec<Item> {
+        if name == "all" {
+            return self.items.drain(..).collect();
+        }
+        match self.find(name) {
+            Some(index) => return vec!(self.take(index, i32::MAX)),
+            None => return vec!(),
+        }
+    }
+
+    // removes `count` items with the given id across stacks and containers, or nothing if there aren't enough
+    pub fn remove(&mut self, id: &str, count: i32) -> bool {
+        if self.count(id) < count {
+            return false;
+        }
+        self.remove_up_to(id, count);
+        return true;
+    }
+
+    // loose items go first, then whatever is packed away, returns how many were removed
+    fn remove_up_to(&mut self, id: &str, count: i32) -> i32 {
+        let mut left = count;
+        while left > 0 {
+            match self.items.iter().rposition(|item| item.id == id) {
+                Some(index) => left -= self.take(index, left).count,
+                None => break,
+            }
+        }
+
+        for item in self.items.iter_mut() {
+            if left == 0 {
+                break;
+            }
+            if let Some(contents) = item.contents.as_mut() {
+                left -= contents.remove_up_to(id, left);
+            }
+        }
+        return count - left;
+    }
+
+    // counts items inside containers too
+    pub fn count(&self, id: &str) -> i32 {
+        return self.items.iter()
+            .map(|item| {
+                let inside = item.contents.as_ref().map_or(0, |c| c.count(id));
+                if item.id == id { item.count + inside } else { inside }
+            })
+            .sum();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.items.is_empty();
+    }
+
+    // every stack on one line, e.g. "Bread x2, Rope"
+    pub fn summary(&self) -> String {
+        return self.items.iter().map(|item| item.label()).collect::<Vec<_>>().join(", ");
+    }
+
+    pub fn weight(&self) -> f32 {
+        return self.items.iter().map(|item| item.weight()).sum();
+    }
+
+    // whether `item` can go in without the total passing `capacity`
+    pub fn fits(&self, item: &Item, capacity: f32) -> bool {
+        return self.weight() + item.weight() <= capacity;
+    }
+
+    // one `item` entry per stack, each container followed by `inside` entries for what it holds
+    pub fn save(&self, out: &mut Writer) {
+        for item in self.items.iter() {
+            out.entry("item", item.saved());
+            for inner in item.contents.iter().flat_map(|c| c.items.iter()) {
+                out.entry("inside", inner.saved());
+            }
+        }
+    }
+
+    // rebuilds the stacks exactly as saved, other keys in the section are ignored
+    pub fn load(section: &Section) -> Result<Inventory, ContentError> {
+        let mut inventory = Inventory::default();
+        for entry in section.entries.iter() {
+            match entry.key.as_str() {
+                "item" => inventory.items.push(Item::from_saved(entry)?),
+                "inside" => match inventory.items.last_mut().and_then(|item| item.contents.as_mut()) {
+                    Some(contents) => contents.items.push(Item::from_saved(entry)?),
+                    None => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+                },
+                _ => {},
+            }
+        }
+        return Ok(inventory);
+    }
+
+    pub fn display(&self) {
+        self.display_nested(0);
+    }
+
+    // container contents are listed under the container, indented
+    fn display_nested(&self, depth: usize) {
+        for item in self.items.iter() {
+            print!("{}{}", "  ".repeat(depth), item.name());
+            execute!(stdout(), cursor::MoveToColumn(15)).expect("failed to execute");
+            print!("x{}", item.count);
+            execute!(stdout(), cursor::MoveToColumn(20)).expect("failed to execute");
+            print!("{:.1}", item.weight());
+            execute!(stdout(), cursor::MoveToColumn(28)).expect("failed to execute");
+            println!("{}", item.describe());
+            if let Some(contents) = &item.contents {
+                contents.display_nested(depth + 1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{content, item};
+
+    fn items(id: &str, count: i32) -> Item {
+        item::load_test_catalogue();
+        let mut item = Item::new(id).unwrap();
+        item.count = count;
+        return item;
+    }
+
+    fn counts(inventory: &Inventory) -> Vec<i32> {
+        return inventory.items.iter().map(|item| item.count).collect();
+    }
+
+    #[test]
+    fn tops_up_stacks_before_starting_new_ones() {
+        let mut inventory = Inventory::new(vec!(items("rope", 3)));
+        inventory.add(items("rope", 4));
+        assert_eq!(counts(&inventory), vec!(5, 2));
+        assert_eq!(inventory.count("rope"), 7);
+    }
+
+    #[test]
+    fn splits_large_counts_into_full_stacks() {
+        let inventory = Inventory::new(vec!(items("bread", 23)));
+        assert_eq!(counts(&inventory), vec!(10, 10, 3));
+    }
+
+    #[test]
+    fn keeps_worn_items_apart() {
+        let mut worn = items("torch", 1);
+        worn.durability = Some(3);
+        let inventory = Inventory::new(vec!(items("torch", 1), worn));
+        assert_eq!(counts(&inventory), vec!(1, 1));
+        assert_eq!(inventory.count("torch"), 2);
+    }
+
+    #[test]
+    fn containers_keep_their_contents() {
+        let mut satchel = items("satchel", 1);
+        satchel.contents.as_mut().unwrap().add(items("bread", 2));
+        let inventory = Inventory::new(vec!(satchel));
+        assert_eq!(inventory.count("bread"), 2);
+    }
+
+    #[test]
+    fn removes_loose_items_before_packed_ones() {
+        let mut satchel = items("satchel", 1);
+        satchel.contents.as_mut().unwrap().add(items("rope", 2));
+        let mut inventory = Inventory::new(vec!(satchel, items("rope", 1)));
+
+        assert!(!inventory.remove("rope", 4));
+        assert_eq!(inventory.count("rope"), 3);
+        assert!(inventory.remove("rope", 2));
+        assert!(inventory.find("rope").is_none());
+        assert_eq!(inventory.count("rope"), 1);
+    }
+
+    #[test]
+    fn round_trips_through_saves() {
+        let mut satchel = items("satchel", 1);
+        satchel.contents.as_mut().unwrap().add(items("bread", 3));
+        let mut torch = items("torch", 1);
+        torch.durability = Some(4);
+        let inventory = Inventory::new(vec!(items("rope", 7), satchel, torch));
+
+        let mut out = Writer::default();
+        out.section("player", "save");
+        inventory.save(&mut out);
+        let loaded = Inventory::load(&content::parse(&out.finish()).unwrap()[0]).unwrap();
+
+        assert_eq!(loaded.summary(), inventory.summary());
+        assert_eq!(counts(&loaded), vec!(5, 2, 1, 1));
+        assert_eq!(loaded.count("bread"), 3);
+        assert_eq!(loaded.get(3).durability, Some(4));
+    }
+
+    #[test]
+    fn rejects_bad_saved_items() {
+        item::load_test_catalogue();
+        for (src, err) in [
+            ("inside = bread 1", ContentError::InvalidValue(2, "bread 1".to_string())),
+            ("item = rope 1\ninside = bread 1", ContentError::InvalidValue(3, "bread 1".to_string())),
+            ("item = anvil 1", ContentError::UnknownItem(2, "anvil".to_string())),
+            ("item = rope 0", ContentError::InvalidValue(2, "rope 0".to_string())),
+            ("item = torch 1 lots", ContentError::InvalidValue(2, "torch 1 lots".to_string())),
+        ] {
+            let sections = content::parse(&format!("[player save]\n{}\n", src)).unwrap();
+            assert_eq!(Inventory::load(&sections[0]).unwrap_err(), err);
+        }
+    }
+
+    #[test]
+    fn take_splits_a_stack() {
+        let mut inventory = Inventory::new(vec!(items("bread", 5)));
+        let taken = inventory.take(0, 2);
+        assert_eq!((taken.count, inventory.count("bread")), (2, 3));
+        assert_eq!(inventory.take(0, i32::MAX).count, 3);
+        assert!(inventory.is_empty());
+    }
+}
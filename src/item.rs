@@ -1,9 +1,19 @@
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt::{self, Display, Formatter};
+use std::sync::OnceLock;
+
+use crate::content::{self, ContentError, Entry, Section};
+use crate::inventory::Inventory;
+use crate::status::Status;
+
+// every item definition, loaded once at startup by `load_catalogue`
+static CATALOGUE: OnceLock<HashMap<String, ItemDef>> = OnceLock::new();
 
 #[derive(Debug, PartialEq)]
 pub enum ItemError {
     NoName,
+    Unknown,
 }
 
 impl Error for ItemError {}
@@ -13,39 +23,398 @@ impl Display for ItemError {
         use ItemError::*;
         match self {
             NoName => write!(f, "No name given to item"),
+            Unknown => write!(f, "No such item in the catalogue"),
+        }
+    }
+}
+
+// what happens when an item is used
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ItemEffect {
+    ShowMap,
+    ShowFacing,
+    // restores health and uses the item up
+    Heal(u8),
+    Light,
+    // removes a status and uses the item up
+    Cure(Status),
+}
+
+impl ItemEffect {
+    pub fn consumes(&self) -> bool {
+        return matches!(self, ItemEffect::Heal(_) | ItemEffect::Cure(_));
+    }
+
+    fn parse(value: &str) -> Option<ItemEffect> {
+        let args: Vec<&str> = value.split_whitespace().collect();
+        match args.as_slice() {
+            ["map"] => Some(ItemEffect::ShowMap),
+            ["compass"] => Some(ItemEffect::ShowFacing),
+            ["heal", amount] => amount.parse().ok().map(ItemEffect::Heal),
+            ["light"] => Some(ItemEffect::Light),
+            ["cure", status] => Status::parse(status).map(ItemEffect::Cure),
+            _ => None,
         }
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Category {
+    Tool,
+    Food,
+    Material,
+    Gear,
+    Misc,
+}
+
+impl Category {
+    fn parse(value: &str) -> Option<Category> {
+        match value {
+            "tool" => Some(Category::Tool),
+            "food" => Some(Category::Food),
+            "material" => Some(Category::Material),
+            "gear" => Some(Category::Gear),
+            "misc" => Some(Category::Misc),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use Category::*;
+        match self {
+            Tool => write!(f, "tool"),
+            Food => write!(f, "food"),
+            Material => write!(f, "material"),
+            Gear => write!(f, "gear"),
+            Misc => write!(f, "misc"),
+        }
+    }
+}
+
+// where a piece of gear is worn or held
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Slot {
+    Head,
+    Body,
+    Hands,
+    Weapon,
+    Offhand,
+}
+
+impl Slot {
+    pub const ALL: [Slot; 5] = [Slot::Head, Slot::Body, Slot::Hands, Slot::Weapon, Slot::Offhand];
+
+    pub fn parse(value: &str) -> Option<Slot> {
+        return Slot::ALL.into_iter().find(|slot| slot.to_string().eq_ignore_ascii_case(value));
+    }
+}
+
+impl Display for Slot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use Slot::*;
+        match self {
+            Head => write!(f, "head"),
+            Body => write!(f, "body"),
+            Hands => write!(f, "hands"),
+            Weapon => write!(f, "weapon"),
+            Offhand => write!(f, "offhand"),
+        }
+    }
+}
+
+// everything that is the same for every copy of an item
 #[derive(Debug)]
-pub struct Item {
+pub struct ItemDef {
+    pub id: String,
     pub name: String,
-    description: String,
-    pub count: i32
+    pub description: String,
+    pub weight: f32,
+    pub value: u32,
+    pub stack: i32,
+    pub category: Category,
+    pub effect: Option<ItemEffect>,
+    // uses before the item breaks, `None` for items that never wear out
+    pub durability: Option<u32>,
+    // weight a container can hold, `None` for items that hold nothing
+    pub capacity: Option<f32>,
+    // gear only, the slot it goes in and what it adds while equipped
+    pub slot: Option<Slot>,
+    pub armor: u8,
+    pub attack: u8,
+}
+
+// reads an optional `key`, falling back to `default` when it is missing
+fn field<T>(section: &Section, key: &str, default: T, parse: impl Fn(&str) -> Option<T>) -> Result<T, ContentError> {
+    match section.get(key) {
+        Some(entry) => match parse(&entry.value) {
+            Some(value) => return Ok(value),
+            None => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+        },
+        None => return Ok(default),
+    }
+}
+
+fn parse_def(section: &Section) -> Result<ItemDef, ContentError> {
+    let name = section.require("name")?.value.clone();
+    if name.is_empty() {
+        return Err(ContentError::MissingKey(section.line, "name".to_string()));
+    }
+
+    let def = ItemDef {
+        id: section.id.clone(),
+        name,
+        description: field(section, "description", "A mysterious object".to_string(), |v| Some(v.to_string()))?,
+        weight: field(section, "weight", 0.0, |v| v.parse().ok().filter(|w: &f32| *w >= 0.0))?,
+        value: field(section, "value", 0, |v| v.parse().ok())?,
+        stack: field(section, "stack", 1, |v| v.parse().ok().filter(|s| *s > 0))?,
+        category: field(section, "category", Category::Misc, Category::parse)?,
+        effect: field(section, "effect", None, |v| ItemEffect::parse(v).map(Some))?,
+        durability: field(section, "durability", None, |v| v.parse().ok().map(Some))?,
+        capacity: field(section, "capacity", None, |v| v.parse().ok().filter(|c: &f32| *c > 0.0).map(Some))?,
+        slot: field(section, "slot", None, |v| Slot::parse(v).map(Some))?,
+        armor: field(section, "armor", 0, |v| v.parse().ok())?,
+        attack: field(section, "attack", 0, |v| v.parse().ok())?,
+    };
+
+    // bonuses only count while worn
+    if def.slot.is_none() && (def.armor > 0 || def.attack > 0) {
+        return Err(ContentError::MissingKey(section.line, "slot".to_string()));
+    }
+
+    // each container keeps its own contents, so they can't share a stack
+    if def.capacity.is_some() && def.stack > 1 {
+        return Err(ContentError::InvalidValue(section.line, "stack".to_string()));
+    }
+    return Ok(def);
+}
+
+// loads the item catalogue, must run before any item is created
+pub fn load_catalogue(path: &str) -> Result<(), Vec<ContentError>> {
+    let sections = content::load(path).map_err(|err| vec!(err))?;
+    let mut errors = vec!();
+    let mut catalogue = HashMap::new();
+
+    for section in sections.iter() {
+        if section.kind != "item" {
+            errors.push(ContentError::InvalidValue(section.line, section.kind.clone()));
+            continue;
+        }
+        if catalogue.contains_key(&section.id) {
+            errors.push(ContentError::Duplicate(section.line, section.id.clone()));
+            continue;
+        }
+        match parse_def(section) {
+            Ok(def) => {
+                catalogue.insert(def.id.clone(), def);
+            },
+            Err(err) => errors.push(err),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    CATALOGUE.set(catalogue).expect("item catalogue loaded twice");
+    return Ok(());
+}
+
+// loads the real catalogue once, however many tests ask for it
+#[cfg(test)]
+pub fn load_test_catalogue() {
+    static LOADED: std::sync::Once = std::sync::Once::new();
+    LOADED.call_once(|| load_catalogue("./src/data/items.txt").unwrap());
+}
+
+fn catalogue() -> &'static HashMap<String, ItemDef> {
+    return CATALOGUE.get().expect("item catalogue not loaded");
+}
+
+// display name for a catalogue id
+pub fn name_of(id: &str) -> &'static str {
+    return &catalogue()[id].name;
+}
+
+#[derive(Clone, Debug)]
+pub struct Item {
+    pub id: String,
+    pub count: i32,
+    // remaining uses for items that wear out
+    pub durability: Option<u32>,
+    // what is inside, for containers only
+    pub contents: Option<Inventory>,
 }
 
 impl Item {
-    pub fn new(item_name: &str, desc: &str) -> Result<Item, ItemError> {
-        if item_name.len() == 0 {
+    pub fn new(id: &str) -> Result<Item, ItemError> {
+        if id.is_empty() {
             return Err(ItemError::NoName)
         }
 
-        if desc.len() == 0 {
-            return Ok(Item { 
-                name: item_name.to_string(), 
-                description: "A mysterious object".to_string(),
-                count: 1
-            })
+        match catalogue().get(id) {
+            Some(def) => return Ok(Item {
+                id: def.id.clone(),
+                count: 1,
+                durability: def.durability,
+                contents: def.capacity.map(|_| Inventory::default()),
+            }),
+            None => return Err(ItemError::Unknown),
+        }
+    }
+
+    // creates an item by catalogue id or display name, ignoring case
+    pub fn lookup(name: &str) -> Option<Item> {
+        let def = catalogue().values()
+            .find(|def| def.id.eq_ignore_ascii_case(name) || def.name.eq_ignore_ascii_case(name))?;
+        return Item::new(&def.id).ok();
+    }
+
+    pub fn def(&self) -> &'static ItemDef {
+        return &catalogue()[&self.id];
+    }
+
+    pub fn name(&self) -> &'static str {
+        return &self.def().name;
+    }
+
+    // weight of the whole stack including anything inside it
+    pub fn weight(&self) -> f32 {
+        let contents = self.contents.as_ref().map_or(0.0, |c| c.weight());
+        return self.def().weight * self.count as f32 + contents;
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        return self.id.eq_ignore_ascii_case(name) || self.name().eq_ignore_ascii_case(name);
+    }
+
+    // `<id> <count> [uses left]` as written to save files, contents are saved separately
+    pub fn saved(&self) -> String {
+        match self.durability {
+            Some(uses) => return format!("{} {} {}", self.id, self.count, uses),
+            None => return format!("{} {}", self.id, self.count),
+        }
+    }
+
+    // `<item> [count]` as written in content files, by id or name with the count defaulting to 1
+    pub fn from_entry(entry: &Entry) -> Result<Item, ContentError> {
+        let args: Vec<&str> = entry.value.split_whitespace().collect();
+        let (name, count) = match args.as_slice() {
+            [name] => (*name, "1"),
+            [name, count] => (*name, *count),
+            _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+        };
+
+        let mut item = match Item::lookup(name) {
+            Some(item) => item,
+            None => return Err(ContentError::UnknownItem(entry.line, name.to_string())),
+        };
+        match count.parse() {
+            Ok(count) if count > 0 => item.count = count,
+            _ => return Err(ContentError::InvalidValue(entry.line, count.to_string())),
         }
+        return Ok(item);
+    }
+
+    pub fn from_saved(entry: &Entry) -> Result<Item, ContentError> {
+        let invalid = || ContentError::InvalidValue(entry.line, entry.value.clone());
+        let args: Vec<&str> = entry.value.split_whitespace().collect();
+        let (id, count, uses) = match args.as_slice() {
+            [id, count] => (*id, *count, None),
+            [id, count, uses] => (*id, *count, Some(*uses)),
+            _ => return Err(invalid()),
+        };
 
-        return Ok(Item { 
-            name: item_name.to_string(), 
-            description: desc.to_string(),
-            count: 1
-        })
+        let mut item = match Item::new(id) {
+            Ok(item) => item,
+            Err(_) => return Err(ContentError::UnknownItem(entry.line, id.to_string())),
+        };
+        item.count = count.parse().ok().filter(|c| *c > 0).ok_or_else(invalid)?;
+        if let Some(uses) = uses {
+            item.durability = Some(uses.parse().map_err(|_| invalid())?);
+        }
+        return Ok(item);
+    }
+
+    // name with the stack size, e.g. "Bread x3"
+    pub fn label(&self) -> String {
+        if self.count > 1 {
+            return format!("{} x{}", self.name(), self.count);
+        }
+        return self.name().to_string();
     }
 
     pub fn describe(&self) -> String {
-        return self.description.clone()
+        return self.def().description.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::Writer;
+
+    fn def(text: &str) -> Result<ItemDef, ContentError> {
+        let sections = content::parse(text).unwrap();
+        return parse_def(&sections[0]);
+    }
+
+    #[test]
+    fn effects_parse() {
+        assert_eq!(ItemEffect::parse("map"), Some(ItemEffect::ShowMap));
+        assert_eq!(ItemEffect::parse("compass"), Some(ItemEffect::ShowFacing));
+        assert_eq!(ItemEffect::parse("heal 10"), Some(ItemEffect::Heal(10)));
+        assert_eq!(ItemEffect::parse("cure poisoned"), Some(ItemEffect::Cure(Status::Poisoned)));
+        assert_eq!(ItemEffect::parse("heal"), None);
+        assert_eq!(ItemEffect::parse("cure hiccups"), None);
+        // only healing and curing use the item up
+        assert!(ItemEffect::Heal(1).consumes() && ItemEffect::Cure(Status::Poisoned).consumes());
+        assert!(!ItemEffect::ShowMap.consumes() && !ItemEffect::Light.consumes());
+    }
+
+    #[test]
+    fn definitions_fill_in_defaults() {
+        let rock = def("[item rock]\nname = Rock\n").unwrap();
+        assert_eq!((rock.weight, rock.value, rock.stack), (0.0, 0, 1));
+        assert_eq!((rock.category, rock.effect, rock.durability), (Category::Misc, None, None));
+
+        let torch = def("[item torch]\nname = Torch\ncategory = tool\neffect = light\ndurability = 30\nstack = 5\n").unwrap();
+        assert_eq!((torch.category, torch.effect, torch.durability, torch.stack), (Category::Tool, Some(ItemEffect::Light), Some(30), 5));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn definitions_reject_bad_fields() {
+        assert!(matches!(def("[item rock]\nname = Rock\neffect = explode\n"), Err(ContentError::InvalidValue(3, _))));
+        assert!(matches!(def("[item rock]\nname = Rock\nstack = 0\n"), Err(ContentError::InvalidValue(3, _))));
+        assert!(matches!(def("[item rock]\nname = Rock\narmor = 2\n"), Err(ContentError::MissingKey(1, _))));
+        assert!(matches!(def("[item sack]\nname = Sack\ncapacity = 5\nstack = 2\n"), Err(ContentError::InvalidValue(1, _))));
+    }
+
+    #[test]
+    fn items_come_from_the_catalogue() {
+        load_test_catalogue();
+        let bread = Item::new("bread").unwrap();
+        assert_eq!((bread.name(), bread.def().effect), ("Bread", Some(ItemEffect::Heal(10))));
+        assert_eq!(Item::new("cake").err(), Some(ItemError::Unknown));
+
+        // by id or name, ignoring case
+        assert_eq!(Item::lookup("Fishing ROD").unwrap().id, "fishing_rod");
+        assert!(Item::lookup("fishing").is_none());
+        assert!(Item::new("satchel").unwrap().contents.is_some());
+    }
+
+    #[test]
+    fn saved_items_keep_their_wear() {
+        load_test_catalogue();
+        let mut torch = Item::new("torch").unwrap();
+        torch.count = 2;
+        torch.durability = Some(7);
+
+        let mut out = Writer::default();
+        out.section("player", "save");
+        out.entry("item", torch.saved());
+        let sections = content::parse(&out.finish()).unwrap();
+        let loaded = Item::from_saved(sections[0].get("item").unwrap()).unwrap();
+        assert_eq!((loaded.id.as_str(), loaded.count, loaded.durability), ("torch", 2, Some(7)));
+    }
+}
@@ -1,5 +1,6 @@
 use core::panic;
 use std::io::{stdin, stdout};
+use std::process;
 use crossterm::{
     execute,
     cursor,
@@ -7,33 +8,195 @@ use crossterm::{
 };
 
 mod player;
+mod bestiary;
+mod character;
 mod item;
+mod inventory;
+mod chest;
+mod clock;
+mod combat;
+mod crafting;
+mod entity;
+mod equipment;
+mod weather;
 mod world;
 mod gamestate;
+mod content;
+mod dialogue;
+mod quest;
+mod questgen;
+mod resource;
+mod rng;
+mod save;
+mod status;
+mod survival;
+mod town;
 
 fn cls() {
     execute!(stdout(), Clear(ClearType::All), cursor::MoveTo(0,0)).unwrap();
 }
 
-fn main() {
-    cls();
-    let gs = gamestate::GameState::new();
-    let world = world::World::new("./src/img/text-map.png".to_string());
-    println!("Input your name:");
+fn report(file: &str, errors: Vec<content::ContentError>) -> ! {
+    for err in errors {
+        println!("{}: {}", file, err);
+    }
+    process::exit(1);
+}
 
-    let mut user_name = String::new();
-    stdin().read_line(&mut user_name).expect("Invalid Name");
+// continues the saved game if there is one and the player wants to, otherwise starts a new one
+fn start(world: world::World) -> player::Player {
+    if save::exists() {
+        println!("Continue your saved game? (y/n)");
+        if read_answer().eq_ignore_ascii_case("y") {
+            match save::load(world) {
+                Ok(player) => return player,
+                Err(err) => report(save::SAVE_PATH, vec!(err)),
+            }
+        }
+    }
 
-    let mut player = match player::Player::new(user_name, world) {
-        Ok(ply) => ply,
+    return create_character(world);
+}
+
+fn read_answer() -> String {
+    let mut answer = String::new();
+    stdin().read_line(&mut answer).expect("Invalid Answer");
+    return answer.trim().to_string();
+}
+
+// walks a new player through naming their character, picking a class and spending attribute points
+fn create_character(world: world::World) -> player::Player {
+    let name = loop {
+        println!("Input your name:");
+        match player::Player::validate_name(&read_answer()) {
+            Ok(name) => break name,
+            Err(err) => println!("{}", err),
+        }
+    };
+
+    println!("Choose your class:");
+    for (i, class) in character::classes().iter().enumerate() {
+        println!("{}. {} - {}", i + 1, class.name, class.description);
+    }
+    let class = loop {
+        let answer = read_answer();
+        let found = match answer.parse::<usize>() {
+            Ok(number) => character::classes().get(number.wrapping_sub(1)),
+            Err(_) => character::find_class(&answer),
+        };
+        match found {
+            Some(class) => break class,
+            None => println!("Pick one of the classes by number or name."),
+        }
+    };
+
+    let mut attributes = class.attributes;
+    let mut points = character::ATTRIBUTE_POINTS;
+    while points > 0 {
+        let current: Vec<String> = character::Attribute::ALL.iter().map(|a| format!("{} {}", a, attributes.get(*a))).collect();
+        println!("{}", current.join(", "));
+        println!("You have {} points to spend, e.g. `strength 2`, or `done` to keep them.", points);
+
+        let answer = read_answer();
+        if answer == "done" {
+            break;
+        }
+        let (attribute, amount) = answer.split_once(' ').unwrap_or((&answer, "1"));
+        let attribute = match character::Attribute::parse(attribute) {
+            Some(attribute) => attribute,
+            None => {
+                println!("There is no such attribute.");
+                continue;
+            },
+        };
+        let value = attributes.get_mut(attribute);
+        match amount.trim().parse::<u8>() {
+            Ok(amount) if amount <= points && *value + amount <= character::MAX_ATTRIBUTE => {
+                *value += amount;
+                points -= amount;
+            },
+            _ => println!("You can spend up to {} points, and no attribute goes above {}.", points, character::MAX_ATTRIBUTE),
+        }
+    }
+
+    println!("Play in hardcore mode? Dying ends the game and deletes the save. (y/n)");
+    let hardcore = read_answer().eq_ignore_ascii_case("y");
+
+    match player::Player::new(&name, class, attributes, hardcore, world) {
+        Ok(ply) => return ply,
         Err(_) => panic!("AAAA")
+    }
+}
+
+fn main() {
+    cls();
+    let _gs = gamestate::GameState::new();
+    let mut world = match world::World::new("./src/img/text-map.png".to_string()) {
+        Ok(world) => world,
+        Err(errors) => report("text-map.png", errors),
     };
 
+    if let Err(errors) = item::load_catalogue("./src/data/items.txt") {
+        report("items.txt", errors);
+    }
+
+    if let Err(errors) = character::load_classes("./src/data/classes.txt") {
+        report("classes.txt", errors);
+    }
+
+    if let Err(errors) = crafting::load_recipes("./src/data/recipes.txt") {
+        report("recipes.txt", errors);
+    }
+
+    if let Err(errors) = resource::load_resources("./src/data/resources.txt") {
+        report("resources.txt", errors);
+    }
+
+    if let Err(errors) = bestiary::load_bestiary("./src/data/creatures.txt", &world) {
+        report("creatures.txt", errors);
+    }
+
+    match dialogue::load_npcs("./src/data/npcs.txt", &world) {
+        Ok((npcs, nodes)) => world.add_npcs(npcs, nodes),
+        Err(errors) => report("npcs.txt", errors),
+    }
+
+    match chest::load_chests("./src/data/chests.txt", &world) {
+        Ok(chests) => world.add_chests(chests),
+        Err(errors) => report("chests.txt", errors),
+    }
+
+    match entity::load_entities("./src/data/entities.txt", &world) {
+        Ok(entities) => world.add_entities(entities),
+        Err(errors) => report("entities.txt", errors),
+    }
+
+    match weather::load_climates("./src/data/weather.txt", &world) {
+        Ok(climates) => world.add_climates(climates),
+        Err(errors) => report("weather.txt", errors),
+    }
+
+    match quest::load_quests("./src/data/quests.txt", &world) {
+        Ok(quests) => {
+            let errors = dialogue::check_quests("./src/data/npcs.txt", |id| quests.iter().any(|q| q.id == id));
+            if !errors.is_empty() {
+                report("npcs.txt", errors);
+            }
+            world.post_quests(quests);
+        },
+        Err(errors) => report("quests.txt", errors),
+    }
+    let generated = questgen::generate_all(&world);
+    world.post_quests(generated);
+
+    let mut player = start(world);
+
     cls();
 
     let (x, y) = player.get_pos();
 
-    player.world.discover(x as usize, y as usize, true);
+    let sight = player.sight();
+    player.world.discover(x as usize, y as usize, true, sight);
     loop {
         let mut cmd = String::new();
         stdin().read_line(&mut cmd).expect("Invalid Command");
@@ -46,5 +209,10 @@ fn main() {
                 println!("{}", err);
             }
         };
+
+        if player.is_game_over() {
+            println!("Game over.");
+            return;
+        }
     }
 }
@@ -1,20 +1,139 @@
 // use std::error::Error;
 use std::io::stdout;
 use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
 use crossterm::{
     execute,
     cursor::{self, MoveTo}};
 
-use crate::item::Item;
-use crate::world::World;
+use crate::bestiary::{self, Difficulty};
+use crate::character::{self, Attribute, Attributes, ClassDef, Skill, Skills};
+use crate::clock::{self, Phase};
+use crate::combat::{Combat, Creature};
+use crate::content::{ContentError, Section, Writer};
+use crate::crafting::{self, Recipe, Station};
+use crate::dialogue::Effect;
+use crate::equipment::Equipment;
+use crate::inventory::Inventory;
+use crate::item::{self, Category, Item, ItemEffect, Slot};
+use crate::quest::{Journal, Quest, Reward};
+use crate::resource::{self, Action};
+use crate::rng::Rng;
+use crate::save;
+use crate::status::{Status, Statuses};
+use crate::survival::{self, Need, Needs};
+use crate::town::Service;
+use crate::weather::Weather;
+use crate::world::{TileName, World};
+
+// wood and rope needed to bridge one river tile
+const BRIDGE_COST: [(&str, i32); 2] = [("wood", 4), ("rope", 1)];
+const CAMPFIRE_COST: [(&str, i32); 1] = [("wood", 2)];
+// turns spent crafting anything
+const CRAFT_TURNS: u64 = 2;
+// how far ahead npcs can tell the weather
+const FORECAST_HOURS: u64 = 8;
+
+// where new games start and where the player wakes up if they die before reaching a town
+const START: (i32, i32) = (164, 40);
+// health the player comes back with after dying
+const RESPAWN_HEALTH: u8 = 50;
+
+// weight the player can carry without slowing down, before strength is added
+const CARRY_CAPACITY: f32 = 20.0;
+const CAPACITY_PER_STRENGTH: f32 = 4.0;
+// health before endurance is added
+const BASE_HEALTH: u8 = 70;
+const HEALTH_PER_ENDURANCE: u8 = 10;
+const MAX_NAME_LENGTH: usize = 20;
+// max health gained with every level
+const HEALTH_PER_LEVEL: u8 = 5;
+// experience for every tile seen for the first time
+const XP_PER_TILE: u32 = 2;
+// experience for every completed quest, on top of its rewards
+const QUEST_XP: u32 = 40;
+// percent chances that grow with each skill level
+const FORAGING_BONUS: i32 = 15;
+const NAVIGATION_AVOID: i32 = 10;
+const SWIM_CHANCE: i32 = 50;
+const SWIM_BONUS: i32 = 10;
+// how long statuses from swimming and eating last
+const SOAKED_TURNS: u32 = 20;
+const EXHAUSTED_TURNS: u32 = 10;
+const WELL_FED_TURNS: u32 = 30;
+// turns between each point of health regained while well-fed
+const WELL_FED_HEAL: u64 = 3;
+const BLESSED_ARMOR: u8 = 2;
+const SOAKED_DODGE: i32 = 2;
+// how much every need builds up with each move, hills take twice as much out of the player
+const NEED_PER_MOVE: u8 = 1;
+const HILLS_DRAIN: u8 = 2;
+// hunger sated for every point of health a food restores
+const FOOD_PER_HEAL: u8 = 3;
+// turns of exhaustion renewed on every move while weary
+const WEARY_TURNS: u32 = 3;
+// how long each way of resting lasts and the health and fatigue recovered every turn of it
+const RESTS: [(&str, u64, u8, u8); 3] = [("rest", 10, 1, 2), ("camp", 30, 2, 2), ("sleep", 60, 3, 2)];
+// gold for a night at an inn
+const INN_PRICE: u32 = 5;
+// turns between each roll for an encounter while resting in the wild at night
+const WATCH_TURNS: u64 = 10;
+// percent of encounters a campfire keeps away
+const CAMPFIRE_AVOID: i32 = 75;
+// past this multiple of the capacity the player can't move at all
+const OVERLOAD: f32 = 1.5;
 
 pub enum PlayerError {
     NoName,
+    InvalidName,
     InvalidMovement,
     InvalidRotation,
     InvalidCommandLength,
     UnknownCommand,
-    OutOfBounds
+    OutOfBounds,
+    NoTown,
+    NotInTown,
+    AlreadyInTown,
+    InTown,
+    NoService,
+    NotEnoughGold,
+    UnknownItem,
+    UnknownQuest,
+    UnknownNpc,
+    ItemNotHeld,
+    NotUsable,
+    Overloaded,
+    NotOnGround,
+    NothingHere,
+    NoContainer,
+    NotAContainer,
+    NotInContainer,
+    ContainerFull,
+    Empty,
+    Locked,
+    Closed,
+    NotEquippable,
+    NotEquipped,
+    UnknownRecipe,
+    MissingMaterials,
+    MissingTool,
+    WrongPlace,
+    NothingToHarvest,
+    Depleted,
+    CantBuildHere,
+    UnknownStructure,
+    SaveFailed,
+    InConversation,
+    InCombat,
+    InvalidChoice,
+    UnknownDifficulty,
+    UnknownAttribute,
+    NoPointsToSpend,
+    AttributeMaxed,
+    TooHeavyToSwim,
+    NothingToCure,
+    NoWater,
+    InvalidSetting,
 }
 
 // impl Error for PlayerError {}
@@ -24,11 +143,55 @@ impl Display for PlayerError {
         use PlayerError::*;
         match self {
             NoName => write!(f, "no player name"),
+            InvalidName => write!(f, "Names are up to {} letters, spaces, hyphens and apostrophes", MAX_NAME_LENGTH),
             InvalidMovement => write!(f, "invalid movement input"),
             InvalidRotation => write!(f, "invalid direction change"),
             InvalidCommandLength => write!(f, "Command too short"),
             OutOfBounds => write!(f, "The movement would put you out of bounds"),
             UnknownCommand => write!(f, "This command does not exist"),
+            NoTown => write!(f, "There is no town here"),
+            NotInTown => write!(f, "You are not in a town"),
+            AlreadyInTown => write!(f, "You are already in town"),
+            InTown => write!(f, "You must leave the town first"),
+            NoService => write!(f, "This town has no such place"),
+            NotEnoughGold => write!(f, "You cannot afford that"),
+            UnknownItem => write!(f, "There is no such item"),
+            UnknownQuest => write!(f, "There is no such notice"),
+            UnknownNpc => write!(f, "There is nobody here by that name"),
+            ItemNotHeld => write!(f, "You are not carrying that"),
+            NotUsable => write!(f, "You can't use that"),
+            Overloaded => write!(f, "You are carrying too much to move"),
+            NotOnGround => write!(f, "There is nothing like that on the ground"),
+            NothingHere => write!(f, "There is nothing here to pick up"),
+            NoContainer => write!(f, "There is nothing by that name to open"),
+            NotAContainer => write!(f, "That can't hold anything"),
+            NotInContainer => write!(f, "There is nothing like that inside"),
+            ContainerFull => write!(f, "It won't fit"),
+            Empty => write!(f, "There is nothing inside"),
+            Locked => write!(f, "It is locked and you have no key"),
+            Closed => write!(f, "You need to open it first"),
+            NotEquippable => write!(f, "You can't equip that"),
+            NotEquipped => write!(f, "You have nothing like that equipped"),
+            UnknownRecipe => write!(f, "You don't know how to make that"),
+            MissingMaterials => write!(f, "You don't have the materials for that"),
+            MissingTool => write!(f, "You don't have the right tools for that"),
+            WrongPlace => write!(f, "You can't make that here"),
+            NothingToHarvest => write!(f, "There is nothing to find here"),
+            Depleted => write!(f, "There is nothing left here, come back later"),
+            CantBuildHere => write!(f, "You can't build that here"),
+            UnknownStructure => write!(f, "You can build a bridge or a campfire"),
+            SaveFailed => write!(f, "The game could not be saved"),
+            InConversation => write!(f, "Pick a numbered reply or say bye"),
+            InCombat => write!(f, "You are in a fight! attack, defend, flee or use <item>"),
+            InvalidChoice => write!(f, "That is not one of the replies"),
+            UnknownDifficulty => write!(f, "There is no such difficulty"),
+            UnknownAttribute => write!(f, "Train strength, agility or endurance"),
+            NoPointsToSpend => write!(f, "You have no points to spend, gain a level first"),
+            AttributeMaxed => write!(f, "That can't be trained any further"),
+            TooHeavyToSwim => write!(f, "You are carrying too much to swim"),
+            NothingToCure => write!(f, "You have nothing that needs curing"),
+            NoWater => write!(f, "There is no water to drink here"),
+            InvalidSetting => write!(f, "Use on or off"),
         }
     }
 }
@@ -36,29 +199,207 @@ impl Display for PlayerError {
 #[derive(Debug)]
 pub struct Player {
     name: String,
+    // id of the class picked at character creation
+    class: String,
+    attributes: Attributes,
     health: u8,
+    // armor before any gear is worn
     armor: u8,
+    gold: u32,
+    reputation: i32,
     pos: (i32, i32),
     pub facing: u8,
-    inventory: Vec<Item>,
+    inventory: Inventory,
+    equipment: Equipment,
+    in_town: bool,
+    journal: Journal,
+    // (npc name, dialogue node) of the conversation in progress
+    conversation: Option<(String, String)>,
+    combat: Option<Combat>,
+    // for everything that should differ between games, like fights
+    rng: Rng,
+    // id of the bestiary difficulty, scales encounters and creatures
+    difficulty: String,
+    // death ends the game for good and deletes the save
+    hardcore: bool,
+    // set once a hardcore character has died
+    game_over: bool,
+    // the town the player respawns at
+    last_town: Option<(i32, i32)>,
+    level: u8,
+    xp: u32,
+    // attribute points from levelling up not yet trained
+    points: u8,
+    skills: Skills,
+    statuses: Statuses,
+    // whether hunger, thirst and fatigue are tracked
+    survival: bool,
+    needs: Needs,
     pub world: World
 }
 
 impl Player {
-    pub fn new(name: String, world: World) -> Result<Player, PlayerError> {
-        if name.len() > 0 {
-            return Ok(Player {
-                name: name,
-                health: 100,
-                armor: 0,
-                pos: (164, 40),
-                facing: 0,
-                inventory: vec!(Item::new("Compass", "Always points the way home").unwrap(), Item::new("Map", "The World Map").unwrap()),
-                world: world
-            })
+    // trims the name read from input and checks it is fit to be shown
+    pub fn validate_name(name: &str) -> Result<String, PlayerError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(PlayerError::NoName);
+        }
+        let allowed = |c: char| c.is_alphabetic() || c == ' ' || c == '-' || c == '\'';
+        if name.chars().count() > MAX_NAME_LENGTH || !name.chars().all(allowed) {
+            return Err(PlayerError::InvalidName);
+        }
+        return Ok(name.to_string());
+    }
+
+    pub fn new(name: &str, class: &ClassDef, attributes: Attributes, hardcore: bool, world: World) -> Result<Player, PlayerError> {
+        let name = Player::validate_name(name)?;
+        let mut equipment = Equipment::default();
+        for item in class.gear.iter() {
+            equipment.equip(item.clone());
+        }
+
+        let mut player = Player {
+            name,
+            class: class.id.clone(),
+            attributes,
+            health: 0,
+            armor: 0,
+            gold: class.gold,
+            reputation: 0,
+            pos: START,
+            facing: 0,
+            equipment,
+            inventory: Inventory::new(class.items.clone()),
+            in_town: false,
+            journal: Journal::default(),
+            conversation: None,
+            combat: None,
+            rng: Rng::from_time(),
+            difficulty: bestiary::DEFAULT_DIFFICULTY.to_string(),
+            hardcore,
+            game_over: false,
+            last_town: None,
+            level: 1,
+            xp: 0,
+            points: 0,
+            skills: Skills::default(),
+            statuses: Statuses::default(),
+            survival: false,
+            needs: Needs::default(),
+            world
+        };
+        player.health = player.max_health();
+        return Ok(player);
+    }
+
+    pub fn save(&self, out: &mut Writer) {
+        out.section("player", "save");
+        out.entry("name", &self.name);
+        out.entry("class", &self.class);
+        self.attributes.save(out);
+        out.entry("health", self.health);
+        out.entry("armor", self.armor);
+        out.entry("gold", self.gold);
+        out.entry("reputation", self.reputation);
+        out.entry("pos", format!("{} {}", self.pos.0, self.pos.1));
+        out.entry("facing", self.facing);
+        out.entry("in_town", self.in_town);
+        out.entry("difficulty", &self.difficulty);
+        out.entry("hardcore", self.hardcore);
+        if let Some((x, y)) = self.last_town {
+            out.entry("last_town", format!("{} {}", x, y));
         }
+        out.entry("level", self.level);
+        out.entry("xp", self.xp);
+        out.entry("points", self.points);
+        self.skills.save(out);
+        self.statuses.save(out);
+        out.entry("survival", self.survival);
+        self.needs.save(out);
+        self.inventory.save(out);
+        self.equipment.save(out);
+
+        self.journal.save(out);
+        self.world.save(out);
+    }
+
+    pub fn load(mut world: World, sections: &[Section]) -> Result<Player, ContentError> {
+        let section = match sections.iter().find(|s| s.kind == "player") {
+            Some(section) => section,
+            None => return Err(ContentError::MissingKey(1, "player".to_string())),
+        };
+
+        let entry = section.require("pos")?;
+        let pos = match entry.value.split_once(' ').map(|(x, y)| (x.parse::<i32>(), y.parse::<i32>())) {
+            Some((Ok(x), Ok(y))) if world.in_bounds(x, y) => (x, y),
+            _ => return Err(ContentError::UnknownTile(entry.line, entry.value.clone())),
+        };
+
+        let entry = section.require("difficulty")?;
+        let difficulty = match bestiary::difficulty(&entry.value) {
+            Some(difficulty) => difficulty.id.clone(),
+            None => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+        };
 
-        return Err(PlayerError::NoName)
+        let last_town = match section.get("last_town") {
+            Some(entry) => match entry.value.split_once(' ').map(|(x, y)| (x.parse::<i32>(), y.parse::<i32>())) {
+                Some((Ok(x), Ok(y))) if world.in_bounds(x, y) && world.town_at(x as usize, y as usize).is_some() => Some((x, y)),
+                _ => return Err(ContentError::UnknownTown(entry.line, entry.value.clone())),
+            },
+            None => None,
+        };
+
+        let entry = section.require("class")?;
+        let class = match character::find_class(&entry.value) {
+            Some(class) => class,
+            None => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+        };
+
+        let entry = section.require("level")?;
+        let level = match entry.value.parse() {
+            Ok(level) if (1..=character::MAX_LEVEL).contains(&level) => level,
+            _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+        };
+
+        world.load(sections)?;
+        let journal = Journal::load(sections, &mut world)?;
+        world.unlock_quests(|id| journal.is_completed(id));
+
+        return Ok(Player {
+            name: section.require("name")?.value.trim().to_string(),
+            class: class.id.clone(),
+            attributes: Attributes::load(section)?,
+            health: saved(section, "health")?,
+            armor: saved(section, "armor")?,
+            gold: saved(section, "gold")?,
+            reputation: saved(section, "reputation")?,
+            pos,
+            facing: saved::<u8>(section, "facing")? % 4,
+            inventory: Inventory::load(section)?,
+            equipment: Equipment::load(section)?,
+            in_town: saved(section, "in_town")?,
+            journal,
+            conversation: None,
+            combat: None,
+            rng: Rng::from_time(),
+            difficulty,
+            hardcore: section.get("hardcore").is_some_and(|e| e.value == "true"),
+            game_over: false,
+            last_town,
+            level,
+            xp: saved(section, "xp")?,
+            points: saved(section, "points")?,
+            skills: Skills::load(section)?,
+            statuses: Statuses::load(section)?,
+            survival: section.get("survival").is_some_and(|e| e.value == "true"),
+            needs: Needs::load(section)?,
+            world,
+        });
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        return self.game_over;
     }
 
     pub fn get_pos(&self) -> (i32, i32){
@@ -66,46 +407,515 @@ impl Player {
     }
 
     pub fn move_facing(&mut self, direction: &str) -> Result<(), PlayerError> {
+        if self.in_town {
+            return Err(PlayerError::InTown);
+        }
+
         self.rotate(direction)?;
-        match self.facing {
-            0 => { // North
-                if self.pos.1 < 100 {
-                    self.pos.1 -= 1;
-                    self.world.discover(self.pos.0.try_into().unwrap(), self.pos.1.try_into().unwrap(), true);
-                    return Ok(());
-                }
-                return Err(PlayerError::OutOfBounds);
+        let (x, y) = self.facing_tile();
+
+        if !self.world.in_bounds(x, y) {
+            return Err(PlayerError::OutOfBounds);
+        }
+        let swimming = !self.world.is_accessible(x as usize, y as usize);
+        if swimming && !self.world.tile_name(x as usize, y as usize).swimmable() {
+            return Err(PlayerError::OutOfBounds);
+        }
+
+        let weight = self.carried();
+        if weight > self.capacity() * OVERLOAD {
+            return Err(PlayerError::Overloaded);
+        }
+        if swimming {
+            if weight > self.capacity() {
+                return Err(PlayerError::TooHeavyToSwim);
             }
-            1 => { // East
-                if self.pos.0 < 100 {
-                    self.pos.0 += 1;
-                    self.world.discover(self.pos.0.try_into().unwrap(), self.pos.1.try_into().unwrap(), true);
-                    return Ok(());
+            if !self.swim() {
+                return Ok(());
+            }
+        }
+
+        self.pos = (x, y);
+        let sight = self.sight();
+        let found = self.world.discover(x as usize, y as usize, true, sight);
+        if found > 0 {
+            self.explore(found);
+        }
+        let weather = self.world.weather_at(x as usize, y as usize);
+        let mut turns = self.world.tile_name(x as usize, y as usize).move_cost() + weather.slowdown();
+        if weather.is_wet() && !self.in_town {
+            self.statuses.add(Status::Soaked, SOAKED_TURNS);
+        }
+        if swimming {
+            self.statuses.add(Status::Soaked, SOAKED_TURNS);
+            turns = 2;
+        } else if weight > self.capacity() {
+            println!("You trudge along under the weight of your pack.");
+            turns += 1;
+        }
+        if self.statuses.has(Status::Exhausted) {
+            turns += 1;
+        }
+        self.pass_time(turns);
+        self.dry_off();
+        if self.survival && !self.drain_needs() {
+            return Ok(());
+        }
+        // whatever was standing there gets the first chance, then everything moves
+        if !self.meet_entity() {
+            let player = if self.in_town { None } else { Some((x as usize, y as usize)) };
+            self.world.move_entities(player, &mut self.rng);
+            if !self.meet_entity() {
+                self.check_encounter(0);
+            }
+        }
+        return Ok(());
+    }
+
+    // rolls whether the player makes it through the shallows ahead, a failed attempt hurts and leaves them where they were
+    fn swim(&mut self) -> bool {
+        let chance = SWIM_CHANCE + SWIM_BONUS * self.skills.level(Skill::Swimming) as i32;
+        let success = self.rng.range(1, 100) <= chance;
+        self.practice(Skill::Swimming);
+        if success {
+            return true;
+        }
+
+        let damage = self.rng.range(2, 6) as u8;
+        self.health = self.health.saturating_sub(damage);
+        self.statuses.add(Status::Soaked, SOAKED_TURNS);
+        self.statuses.add(Status::Exhausted, EXHAUSTED_TURNS);
+        println!("The water drags you back to the bank. You lose {} health.", damage);
+        self.pass_time(1);
+        if self.health == 0 {
+            self.die("You are swept away in the shallows.");
+        }
+        return false;
+    }
+
+    // advances the world and counts down every status, turn by turn
+    fn pass_time(&mut self, turns: u64) {
+        for _ in 0..turns {
+            self.world.advance(1);
+            self.world.update_weather(&mut self.rng);
+            // poison wears the player down but never finishes them off
+            if self.statuses.has(Status::Poisoned) && self.health > 1 {
+                self.health -= 1;
+            }
+            if self.statuses.has(Status::WellFed) && self.world.turn().is_multiple_of(WELL_FED_HEAL) {
+                self.health = self.health.saturating_add(1).min(self.max_health());
+            }
+            for status in self.statuses.tick() {
+                println!("You are no longer {}.", status);
+            }
+            // lights only burn down while they are needed
+            if self.world.phase() == Phase::Night {
+                for item in self.equipment.burn_lights() {
+                    println!("Your {} burns out.", item.name());
                 }
-                return Err(PlayerError::OutOfBounds);
             }
-            2 => { // South
-                if self.pos.1 > 0 {
-                    if self.world.is_accessible(self.pos.0.try_into().unwrap(), (self.pos.1 + 1 ).try_into().unwrap()) {
-                        self.pos.1 += 1;
-                        self.world.discover(self.pos.0.try_into().unwrap(), self.pos.1.try_into().unwrap(), true);
-                        return Ok(());
-                    }
-                    return Err(PlayerError::OutOfBounds);
+        }
+    }
+
+    // builds up every need after a move and applies their penalties, returns false if the player collapsed
+    fn drain_needs(&mut self) -> bool {
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        let amount = if self.world.tile_name(x, y) == TileName::Hills { NEED_PER_MOVE * HILLS_DRAIN } else { NEED_PER_MOVE };
+        for need in Need::ALL {
+            if let Some(feeling) = self.needs.add(need, amount) {
+                println!("You are {}.", feeling);
+            }
+        }
+
+        if self.needs.stage(Need::Fatigue) >= 2 {
+            self.statuses.add(Status::Exhausted, WEARY_TURNS);
+        }
+        // a need left at its worst eats away at health
+        for need in Need::ALL {
+            if self.needs.get(need) < survival::MAX_NEED {
+                continue;
+            }
+            self.health = self.health.saturating_sub(1);
+            if self.health == 0 {
+                self.die(&format!("You collapse from {}.", need));
+                return false;
+            }
+        }
+        return true;
+    }
+
+    fn drink(&mut self) -> Result<(), PlayerError> {
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        if self.in_town {
+            println!("You drink your fill from the town well.");
+        } else {
+            let spots = [(x, y), (x, y - 1), (x + 1, y), (x, y + 1), (x - 1, y)];
+            if !spots.iter().any(|&(x, y)| self.world.tile_name(x, y) == TileName::Water) {
+                return Err(PlayerError::NoWater);
+            }
+            println!("You drink your fill from the river.");
+        }
+        self.needs.relieve(Need::Thirst, survival::MAX_NEED);
+        self.pass_time(1);
+        return Ok(());
+    }
+
+    // what the next few hours hold, as a local would put it
+    fn forecast(&self) {
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        let hours = self.world.forecast(x, y, FORECAST_HOURS);
+        let now = hours[0];
+        let hour = clock::hour(self.world.turn());
+        match hours.iter().position(|w| *w != now) {
+            Some(later) if hours[later] == Weather::Clear => {
+                println!("\"This {} should clear up by about {:02}:00.\"", now, (hour + later as u64) % 24);
+            },
+            Some(later) => println!("\"Expect {} by about {:02}:00.\"", hours[later].noun(), (hour + later as u64) % 24),
+            None if now == Weather::Clear => println!("\"Clear skies for a while yet, by the look of it.\""),
+            None => println!("\"This {} will be with us a while yet.\"", now),
+        }
+    }
+
+    fn set_survival(&mut self, setting: Option<&str>) -> Result<(), PlayerError> {
+        match setting {
+            Some("on") => self.survival = true,
+            Some("off") => self.survival = false,
+            Some(_) => return Err(PlayerError::InvalidSetting),
+            None => {},
+        }
+        println!("Hunger, thirst and fatigue are {}.", if self.survival { "on" } else { "off" });
+        return Ok(());
+    }
+
+    // a campfire on the player's tile dries their clothes
+    fn dry_off(&mut self) {
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        if self.world.has_campfire(x, y) && self.statuses.remove(Status::Soaked) {
+            println!("You dry off by the fire.");
+        }
+    }
+
+    fn add_status(&mut self, status: Status, turns: u32) {
+        if self.statuses.add(status, turns) {
+            println!("You are {}.", status);
+        } else {
+            println!("Your blessing keeps you from being {}.", status);
+        }
+    }
+
+    // rewards seeing new tiles, with experience and practice at finding the way
+    fn explore(&mut self, tiles: u32) {
+        self.practice(Skill::Navigation);
+        self.gain_xp(tiles * XP_PER_TILE);
+    }
+
+    fn practice(&mut self, skill: Skill) {
+        if self.skills.practice(skill) {
+            println!("Your {} improves to level {}.", skill, self.skills.level(skill));
+        }
+    }
+
+    fn gain_xp(&mut self, amount: u32) {
+        if self.level >= character::MAX_LEVEL {
+            return;
+        }
+        self.xp += amount;
+        while self.level < character::MAX_LEVEL && self.xp >= character::xp_for_level(self.level + 1) {
+            self.level += 1;
+            self.points += 1;
+            self.health = self.health.saturating_add(HEALTH_PER_LEVEL);
+            println!("You reach level {}! Use train <attribute> to spend your point.", self.level);
+        }
+    }
+
+    // spends a point from levelling up on an attribute
+    fn train(&mut self, name: &str) -> Result<(), PlayerError> {
+        let attribute = match Attribute::parse(name) {
+            Some(attribute) => attribute,
+            None => return Err(PlayerError::UnknownAttribute),
+        };
+        if self.points == 0 {
+            return Err(PlayerError::NoPointsToSpend);
+        }
+        if self.attributes.get(attribute) >= character::MAX_TRAINED {
+            return Err(PlayerError::AttributeMaxed);
+        }
+
+        self.points -= 1;
+        *self.attributes.get_mut(attribute) += 1;
+        if attribute == Attribute::Endurance {
+            self.health = self.health.saturating_add(HEALTH_PER_ENDURANCE);
+        }
+        println!("Your {} rises to {}.", attribute, self.attributes.get(attribute));
+        return Ok(());
+    }
+
+    fn difficulty(&self) -> &'static Difficulty {
+        return bestiary::difficulty(&self.difficulty).unwrap();
+    }
+
+    fn set_difficulty(&mut self, id: Option<&str>) -> Result<(), PlayerError> {
+        let id = match id {
+            Some(id) => id,
+            None => {
+                for difficulty in bestiary::difficulties() {
+                    let marker = if difficulty.id == self.difficulty { " (current)" } else { "" };
+                    println!("{}{}", difficulty.id, marker);
                 }
-                return Err(PlayerError::OutOfBounds);
+                return Ok(());
+            },
+        };
+
+        match bestiary::difficulty(id) {
+            Some(difficulty) => {
+                self.difficulty = difficulty.id.clone();
+                println!("Difficulty set to {}.", difficulty.id);
+                return Ok(());
+            },
+            None => return Err(PlayerError::UnknownDifficulty),
+        }
+    }
+
+    // rolls for something attacking the player, `avoided` is a percent of encounters kept away on top of navigation
+    fn check_encounter(&mut self, avoided: i32) -> bool {
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        let avoided = (avoided + NAVIGATION_AVOID * self.skills.level(Skill::Navigation) as i32).min(100);
+        if let Some(creature) = bestiary::encounter(&self.world, x, y, self.difficulty(), avoided, &mut self.rng) {
+            self.start_fight(Combat::new(creature, None));
+            return true;
+        }
+        return false;
+    }
+
+    // rest, camp or sleep, recovering every turn until done or until something finds the player
+    fn rest(&mut self, kind: &str) -> Result<(), PlayerError> {
+        let &(_, turns, heal, relief) = RESTS.iter().find(|r| r.0 == kind).ok_or(PlayerError::UnknownCommand)?;
+        if kind == "camp" && self.in_town {
+            return Err(PlayerError::InTown);
+        }
+        // a proper bed in town costs the same as at the inn
+        if kind == "sleep" && self.in_town {
+            return self.stay_at_inn();
+        }
+
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        // nothing gets at the player inside a town
+        let player = if self.in_town { None } else { Some((x, y)) };
+        let avoided = if self.world.has_campfire(x, y) { CAMPFIRE_AVOID } else { 0 };
+        match kind {
+            "rest" => println!("You sit down to catch your breath."),
+            "camp" => println!("You make camp for a while."),
+            _ => println!("You lie down to sleep."),
+        }
+
+        for turn in 1..=turns {
+            self.pass_time(1);
+            self.health = self.health.saturating_add(heal).min(self.max_health());
+            self.needs.relieve(Need::Fatigue, relief);
+            self.world.move_entities(player, &mut self.rng);
+            if player.is_none() {
+                continue;
+            }
+            let watch = turn.is_multiple_of(WATCH_TURNS) && self.world.phase() == Phase::Night;
+            if self.meet_entity() || (watch && self.check_encounter(avoided)) {
+                println!("Your rest is cut short.");
+                return Ok(());
             }
-            3 => { // West
-                if self.pos.0 > 0 {
-                    self.pos.0 -= 1;
-                    self.world.discover(self.pos.0.try_into().unwrap(), self.pos.1.try_into().unwrap(), true);
+        }
+        self.dry_off();
+        println!("You get up feeling refreshed.");
+        return Ok(());
+    }
+
+    // a night in a bed at the town's inn, sleeping as long as a `sleep` outside
+    fn stay_at_inn(&mut self) -> Result<(), PlayerError> {
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        if !self.world.town_at(x, y).is_some_and(|town| town.has_service(Service::Inn)) {
+            return Err(PlayerError::NoService);
+        }
+        if self.gold < INN_PRICE {
+            return Err(PlayerError::NotEnoughGold);
+        }
+        self.gold -= INN_PRICE;
+        let &(_, turns, _, _) = RESTS.iter().find(|r| r.0 == "sleep").ok_or(PlayerError::UnknownCommand)?;
+        self.pass_time(turns);
+        self.health = self.max_health();
+        self.needs.relieve(Need::Fatigue, survival::MAX_NEED);
+        println!("You pay {} gold for a warm bed and wake up fully rested.", INN_PRICE);
+        return Ok(());
+    }
+
+    // starts a fight with a roaming creature sharing the player's tile, returns whether one did
+    fn meet_entity(&mut self) -> bool {
+        if self.in_town {
+            return false;
+        }
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        let turn = self.world.turn();
+        let entity = self.world.entities_at(x, y).into_iter()
+            .find(|e| e.is_hostile() && turn >= e.calm_until)
+            .cloned();
+        let Some(entity) = entity else {
+            return false;
+        };
+
+        let def = bestiary::find(entity.creature.as_ref().unwrap()).unwrap();
+        let mut creature = Creature::spawn(def, self.difficulty(), &mut self.rng);
+        creature.name = entity.name.clone();
+        self.start_fight(Combat::new(creature, Some(entity.id)));
+        return true;
+    }
+
+    fn start_fight(&mut self, mut combat: Combat) {
+        combat.record(format!("A {} attacks you!", combat.creature.name.to_lowercase()));
+        println!("attack, defend, flee or use <item>");
+        self.combat = Some(combat);
+    }
+
+    // handles input while fighting: attack, defend, flee or use <item>, plus a look at the log or status
+    fn fight(&mut self, input: &str) -> Result<(), PlayerError> {
+        let (action, rest) = input.split_once(' ').unwrap_or((input, ""));
+        match action {
+            "attack" => {
+                let attack = self.attack();
+                self.combat.as_mut().unwrap().player_attack(attack, &mut self.rng);
+            },
+            "defend" => {
+                let combat = self.combat.as_mut().unwrap();
+                combat.defending = true;
+                combat.record("You raise your guard.".to_string());
+            },
+            "flee" => {
+                let dodge = self.dodge();
+                let combat = self.combat.as_mut().unwrap();
+                if combat.try_flee(dodge, &mut self.rng) {
+                    println!("You get away.");
+                    let turn = self.world.turn();
+                    if let Some(entity) = combat.entity.as_ref().and_then(|id| self.world.entity_mut(id)) {
+                        entity.calm(turn);
+                    }
+                    self.combat = None;
                     return Ok(());
                 }
-                return Err(PlayerError::OutOfBounds);
+                combat.record("You try to run but can't get away.".to_string());
+            },
+            "use" => {
+                if rest.trim().is_empty() {return Err(PlayerError::InvalidCommandLength)}
+                self.use_item(rest.trim())?;
+            },
+            "log" => {
+                for line in self.combat.as_ref().unwrap().log.iter() {
+                    println!("{}", line);
+                }
+                return Ok(());
+            },
+            "status" => {
+                self.display_status();
+                return Ok(());
+            },
+            _ => return Err(PlayerError::InCombat),
+        }
+
+        if self.combat.as_ref().unwrap().is_won() {
+            self.win_fight();
+            return Ok(());
+        }
+
+        let armor = self.armor();
+        let dodge = self.dodge();
+        let combat = self.combat.as_mut().unwrap();
+        let damage = combat.creature_attack(armor, dodge, &mut self.rng);
+        self.health = self.health.saturating_sub(damage);
+        println!("{} health left, the {} is {}.", self.health, combat.creature.name.to_lowercase(), combat.condition());
+        if let Some((chance, turns)) = combat.creature.poison {
+            if damage > 0 && self.health > 0 && self.rng.range(1, 100) <= chance {
+                self.add_status(Status::Poisoned, turns);
             }
-            _ => {
-                return Err(PlayerError::InvalidMovement);
+        }
+        if self.health == 0 {
+            self.lose_fight();
+        }
+        return Ok(());
+    }
+
+    fn win_fight(&mut self) {
+        let Combat { creature, entity, .. } = self.combat.take().unwrap();
+        let turn = self.world.turn();
+        if let Some(entity) = entity.as_ref().and_then(|id| self.world.entity_mut(id)) {
+            entity.defeat(turn);
+        }
+        println!("The {} is defeated.", creature.name.to_lowercase());
+        for (id, chance) in creature.loot.iter() {
+            if self.rng.range(1, 100) > *chance {
+                continue;
+            }
+            let item = Item::new(id).unwrap();
+            println!("You take {}.", item.label());
+            self.inventory.add(item);
+        }
+        if creature.gold > 0 {
+            println!("You find {} gold.", creature.gold);
+            self.gold += creature.gold;
+        }
+        self.journal.record_kill(&[&creature.name, &creature.plural]);
+        self.gain_xp(creature.xp);
+    }
+
+    fn lose_fight(&mut self) {
+        let Combat { creature, entity, .. } = self.combat.take().unwrap();
+        let turn = self.world.turn();
+        if let Some(entity) = entity.as_ref().and_then(|id| self.world.entity_mut(id)) {
+            entity.calm(turn);
+        }
+        self.die(&format!("You are struck down by the {}.", creature.name.to_lowercase()));
+    }
+
+    // called once health reaches 0, ends a hardcore game and otherwise sends the player back to town
+    fn die(&mut self, cause: &str) {
+        println!("{}", cause);
+        self.combat = None;
+        self.conversation = None;
+
+        if self.hardcore {
+            println!("You have died. Your story ends here.");
+            if save::delete().is_err() {
+                println!("The save could not be deleted.");
+            }
+            self.game_over = true;
+            return;
+        }
+
+        // the pack stays where the player fell, gear that is worn comes along
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        let dropped = self.inventory.take_all();
+        if !dropped.is_empty() {
+            for item in dropped {
+                self.world.items_at_mut(x, y).add(item);
             }
+            println!("Everything you carried lies where you fell, in the {}.", self.world.tile_name(x, y).label());
+        }
+
+        self.pos = self.last_town.unwrap_or(START);
+        self.in_town = false;
+        self.health = RESPAWN_HEALTH;
+        self.needs = Needs::default();
+        self.statuses = Statuses::default();
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        match self.world.town_at(x, y) {
+            Some(town) => println!("You wake up in {}, bruised but alive.", town.name),
+            None => println!("You wake up, bruised but alive."),
+        }
+        let sight = self.sight();
+        self.world.discover(x, y, true, sight);
+    }
+
+    // the tile directly ahead of the player
+    fn facing_tile(&self) -> (i32, i32) {
+        match self.facing {
+            0 => return (self.pos.0, self.pos.1 - 1), // North
+            1 => return (self.pos.0 + 1, self.pos.1), // East
+            2 => return (self.pos.0, self.pos.1 + 1), // South
+            _ => return (self.pos.0 - 1, self.pos.1), // West
         }
     }
 
@@ -144,110 +954,1055 @@ impl Player {
     }
 
     pub fn display_inventory(&self) {
-        let mut inventory = self.inventory.iter();
-        loop {
-            let item = inventory.next();
-            if item.is_none() {
-                break;
-            }
+        self.inventory.display();
+        println!("Total weight: {:.1} / {:.1}", self.carried(), self.capacity());
+        if self.carried() > self.capacity() * OVERLOAD {
+            println!("You are carrying too much to move.");
+        } else if self.carried() > self.capacity() {
+            println!("You are encumbered.");
+        }
+    }
+
+    fn capacity(&self) -> f32 {
+        return CARRY_CAPACITY + self.attributes.strength as f32 * CAPACITY_PER_STRENGTH;
+    }
+
+    // worn gear weighs as much as packed gear
+    fn carried(&self) -> f32 {
+        return self.inventory.weight() + self.equipment.weight();
+    }
+
+    pub fn armor(&self) -> u8 {
+        let blessing = if self.statuses.has(Status::Blessed) { BLESSED_ARMOR } else { 0 };
+        return self.armor.saturating_add(self.equipment.armor()).saturating_add(blessing);
+    }
 
-            print!("{}", item.unwrap().name);
-            execute!(stdout(), cursor::MoveToColumn(15)).expect("failed to execute");
-            println!("{}", item.unwrap().describe());
+    pub fn attack(&self) -> u8 {
+        let mut attack = self.equipment.attack() + self.attributes.strength / 2;
+        if self.statuses.has(Status::Exhausted) {
+            attack = attack.saturating_sub(1);
+        }
+        if self.survival && self.needs.stage(Need::Hunger) >= 2 {
+            attack = attack.saturating_sub(1);
         }
+        return attack;
     }
 
-    pub fn check_command(&mut self, cmd: String) -> Result<(), PlayerError>{
-        if cmd.len() < 4 {
-            return Err(PlayerError::InvalidCommandLength);
+    // how far the player can see, a light lets them find their way at night
+    pub fn sight(&self) -> usize {
+        let mut sight = self.world.phase().sight();
+        if sight == 0 && self.equipment.has_effect(ItemEffect::Light) {
+            sight = 1;
         }
-        let mut cmd_args = cmd.split_whitespace();
-    
-        let base_cmd = cmd_args.next().unwrap();
-    
-        match base_cmd {
-            "help" => {
-                println!("Avalible commands:\nmove <forward/backward/left/right>\ninventory\nstatus");
-                Ok(())
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        return sight.saturating_sub(self.world.weather_at(x, y).sight_penalty());
+    }
+
+    // added to the player's evasion and flight rolls
+    fn dodge(&self) -> i32 {
+        let mut dodge = self.attributes.agility as i32 / 2;
+        if self.statuses.has(Status::Soaked) {
+            dodge -= SOAKED_DODGE;
+        }
+        if self.survival && self.needs.stage(Need::Thirst) >= 2 {
+            dodge -= 1;
+        }
+        return dodge;
+    }
+
+    pub fn max_health(&self) -> u8 {
+        return BASE_HEALTH + self.attributes.endurance * HEALTH_PER_ENDURANCE + (self.level - 1) * HEALTH_PER_LEVEL;
+    }
+
+    fn display_status(&self) {
+        println!("Name: {}", self.name);
+        println!("Class: {}", character::find_class(&self.class).unwrap().name);
+        println!("Level: {}", self.level);
+        if self.level < character::MAX_LEVEL {
+            println!("Experience: {}/{}", self.xp, character::xp_for_level(self.level + 1));
+        }
+        println!("Health: {}/{}", self.health, self.max_health());
+        println!("Armor: {}", self.armor());
+        println!("Attack: {}", self.attack());
+        println!("Gold: {}", self.gold);
+        println!("Reputation: {}", self.reputation);
+        for attribute in Attribute::ALL {
+            let name = attribute.to_string();
+            print!("{}{}:", name[..1].to_uppercase(), &name[1..]);
+            execute!(stdout(), cursor::MoveToColumn(12)).expect("failed to execute");
+            println!("{}", self.attributes.get(attribute));
+        }
+        if self.points > 0 {
+            println!("Points to train: {}", self.points);
+        }
+        if self.survival {
+            for need in Need::ALL {
+                let name = need.to_string();
+                print!("{}{}:", name[..1].to_uppercase(), &name[1..]);
+                execute!(stdout(), cursor::MoveToColumn(12)).expect("failed to execute");
+                println!("{} ({})", self.needs.get(need), self.needs.feeling(need));
+            }
+        }
+        for skill in Skill::ALL {
+            let name = skill.to_string();
+            print!("{}{}:", name[..1].to_uppercase(), &name[1..]);
+            execute!(stdout(), cursor::MoveToColumn(12)).expect("failed to execute");
+            println!("{}", self.skills.level(skill));
+        }
+        println!("Time: {}", clock::describe(self.world.turn()));
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        println!("Weather: {}", self.world.weather_at(x, y));
+        println!("Difficulty: {}{}", self.difficulty, if self.hardcore { " (hardcore)" } else { "" });
+        let statuses: Vec<String> = self.statuses.iter()
+            .map(|(status, left)| format!("{} ({} turns)", status, left))
+            .collect();
+        if !statuses.is_empty() {
+            println!("Effects: {}", statuses.join(", "));
+        }
+        for slot in Slot::ALL {
+            print!("{}:", slot);
+            execute!(stdout(), cursor::MoveToColumn(10)).expect("failed to execute");
+            match self.equipment.get(slot) {
+                Some(item) => println!("{}", item.name()),
+                None => println!("-"),
+            }
+        }
+    }
+
+    fn equip(&mut self, name: &str) -> Result<(), PlayerError> {
+        let index = match self.inventory.find(name) {
+            Some(index) => index,
+            None => return Err(PlayerError::ItemNotHeld),
+        };
+        if self.inventory.get(index).def().slot.is_none() {
+            return Err(PlayerError::NotEquippable);
+        }
+
+        let item = self.inventory.take(index, 1);
+        println!("You equip the {}.", item.name());
+        if let Some(old) = self.equipment.equip(item) {
+            println!("You put the {} away.", old.name());
+            self.inventory.add(old);
+        }
+        return Ok(());
+    }
+
+    fn unequip(&mut self, name: &str) -> Result<(), PlayerError> {
+        match self.equipment.unequip(name) {
+            Some(item) => {
+                println!("You put the {} away.", item.name());
+                self.inventory.add(item);
+                return Ok(());
             },
-            "inventory" => {
-                self.display_inventory();
-                Ok(())
+            None => return Err(PlayerError::NotEquipped),
+        }
+    }
+
+    fn examine(&self, name: &str) -> Result<(), PlayerError> {
+        let item = match self.inventory.find(name) {
+            Some(index) => self.inventory.get(index),
+            None => return Err(PlayerError::ItemNotHeld),
+        };
+
+        let def = item.def();
+        println!("{} ({})", def.name, def.category);
+        println!("{}", def.description);
+        println!("Weight: {}", def.weight);
+        println!("Value: {} gold", def.value);
+        println!("Stacks up to: {}", def.stack);
+        if let (Some(left), Some(max)) = (item.durability, def.durability) {
+            println!("Durability: {}/{}", left, max);
+        }
+        if let (Some(contents), Some(capacity)) = (&item.contents, def.capacity) {
+            println!("Holds: {:.1} / {:.1}", contents.weight(), capacity);
+        }
+        if let Some(slot) = def.slot {
+            println!("Slot: {}", slot);
+            println!("Armor: +{}  Attack: +{}", def.armor, def.attack);
+        }
+        return Ok(());
+    }
+
+    fn use_item(&mut self, name: &str) -> Result<(), PlayerError> {
+        let index = match self.inventory.find(name) {
+            Some(index) => index,
+            None => return Err(PlayerError::ItemNotHeld),
+        };
+
+        let effect = match self.inventory.get(index).def().effect {
+            Some(effect) => effect,
+            None => return Err(PlayerError::NotUsable),
+        };
+
+        match effect {
+            ItemEffect::ShowMap => {
+                self.world.print_map();
+                execute!(
+                    stdout(),
+                    MoveTo((self.pos.0 + 1).try_into().unwrap(), (self.pos.1 + 1).try_into().unwrap())
+                ).unwrap();
+                print!("☺");
+                execute!(
+                    stdout(),
+                    MoveTo((0).try_into().unwrap(), (52).try_into().unwrap())
+                ).unwrap();
             },
-            "status" => {
-                println!("Name: {}", self.name);
-                println!("Health: {}", self.health);
-                println!("Armor: {}", self.armor);
-                Ok(())
+            ItemEffect::ShowFacing => {
+                let key = ["North", "East", "South", "West"];
+                println!("You are facing {}", key[self.facing as usize]);
             },
-            "use" => {
-                let item = cmd_args.next();
-                if item.is_none() {return Err(PlayerError::InvalidCommandLength)}
-                let item = item.unwrap();
-                match item {
-                    "map" => {
-                        self.world.print_map();
-                        execute!(
-                            stdout(),
-                            MoveTo((self.pos.0 + 1).try_into().unwrap(), (self.pos.1 + 1).try_into().unwrap())
-                        ).unwrap();
-                        print!("☺");
-                        execute!(
-                            stdout(),
-                            MoveTo((0).try_into().unwrap(), (52).try_into().unwrap())
-                        ).unwrap();
-                        return Ok(());
-                    },
-                    "compass" => {
-                        let key = ["North", "South", "East", "West"];
-                        println!("You are facing {}", key[self.facing as usize]);
-                        return Ok(());
-                    },
-                    _ => {return Err(PlayerError::InvalidMovement);}
+            ItemEffect::Heal(amount) => {
+                self.health = self.health.saturating_add(amount).min(self.max_health());
+                println!("You use the {} and feel better.", self.inventory.get(index).name());
+                if self.inventory.get(index).def().category == Category::Food {
+                    self.add_status(Status::WellFed, WELL_FED_TURNS);
+                    self.needs.relieve(Need::Hunger, amount.saturating_mul(FOOD_PER_HEAL));
                 }
-            }
-            "look" => {
-                let direction = cmd_args.next();
-                if direction.is_none() {return Err(PlayerError::InvalidCommandLength)}
-                let direction = direction.unwrap();
-    
-                if direction != "forward"
-                        && direction != "forwards"
-                        && direction != "up"
-                        && direction != "backward"
-                        && direction != "backwards"
-                        && direction != "down"
-                        && direction != "back"
-                        && direction != "left"
-                        && direction != "right" {
-                    return Err(PlayerError::InvalidMovement);
+            },
+            ItemEffect::Cure(status) => {
+                if !self.statuses.remove(status) {
+                    return Err(PlayerError::NothingToCure);
                 }
-                match self.rotate(direction) {
-                    Err(_) => return Err(PlayerError::InvalidMovement),
-                    Ok(_) => {
-                        match self.facing {
-                            0 => {
-                                self.world.display_tile(self.pos.0.try_into().unwrap(), (self.pos.1 - 1).try_into().unwrap());
-                                return Ok(());
-                            },
-                            1 => {
-                                self.world.display_tile((self.pos.0 - 1).try_into().unwrap(), (self.pos.1).try_into().unwrap());
-                                return Ok(());
-                            },
-                            2 => {
-                                self.world.display_tile(self.pos.0.try_into().unwrap(), (self.pos.1 + 1).try_into().unwrap());
-                                return Ok(());
-                            },
-                            3 => {
-                                self.world.display_tile((self.pos.0 + 1).try_into().unwrap(), (self.pos.1).try_into().unwrap());
-                                return Ok(());
-                            },
-                            _ => {
-                                return Err(PlayerError::InvalidMovement);
-                            }
-                        }
-                    },
-                };
+                println!("You use the {} and are no longer {}.", self.inventory.get(index).name(), status);
+            },
+            // lights are held up rather than used up
+            ItemEffect::Light => {
+                println!("The {} casts a warm light around you.", self.inventory.get(index).name());
+                return self.equip(name);
+            },
+        }
+
+        if effect.consumes() {
+            self.inventory.take(index, 1);
+            return Ok(());
+        }
+
+        // only one item of the stack wears down
+        if self.inventory.get(index).durability.is_some() {
+            let mut item = self.inventory.take(index, 1);
+            let durability = item.durability.unwrap().saturating_sub(1);
+            if durability == 0 {
+                println!("Your {} is worn out.", item.name());
+                return Ok(());
+            }
+            item.durability = Some(durability);
+            self.inventory.add(item);
+        }
+        return Ok(());
+    }
+
+    fn enter_town(&mut self) -> Result<(), PlayerError> {
+        if self.in_town {
+            return Err(PlayerError::AlreadyInTown);
+        }
+
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        match self.world.town_at(x, y) {
+            Some(town) => {
+                self.in_town = true;
+                self.last_town = Some(self.pos);
+                town.print_menu();
+                let people: Vec<&str> = self.world.npcs_at(x, y, Some(&town.name)).iter().map(|n| n.name.as_str()).collect();
+                if !people.is_empty() {
+                    println!("People here: {}", people.join(", "));
+                }
+                return Ok(());
+            },
+            None => return Err(PlayerError::NoTown),
+        }
+    }
+
+    fn leave_town(&mut self) -> Result<(), PlayerError> {
+        if !self.in_town {
+            return Err(PlayerError::NotInTown);
+        }
+
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        self.in_town = false;
+        println!("You leave {}.", self.world.town_at(x, y).unwrap().name);
+        return Ok(());
+    }
+
+    fn visit(&mut self, place: &str) -> Result<(), PlayerError> {
+        if !self.in_town {
+            return Err(PlayerError::NotInTown);
+        }
+
+        let service = match Service::parse(place) {
+            Some(service) => service,
+            None => return Err(PlayerError::NoService),
+        };
+
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        let town = self.world.town_at(x, y).unwrap();
+        if !town.has_service(service) {
+            return Err(PlayerError::NoService);
+        }
+
+        match service {
+            Service::Inn => return self.stay_at_inn(),
+            Service::Shop => {
+                println!("For sale:");
+                for id in town.wares() {
+                    let item = Item::new(id).unwrap();
+                    print!("{}", item.name());
+                    execute!(stdout(), cursor::MoveToColumn(15)).expect("failed to execute");
+                    print!("{} gold", item.def().value);
+                    execute!(stdout(), cursor::MoveToColumn(25)).expect("failed to execute");
+                    println!("{}", item.describe());
+                }
+                println!("buy <item>");
+            },
+            Service::Forge => {
+                println!("The smith lets you work at the forge for free.");
+                self.display_recipes();
+            },
+            Service::NoticeBoard => {
+                let notices: Vec<_> = town.quests.iter().filter(|q| q.unlocked).collect();
+                if notices.is_empty() {
+                    println!("The notice board is empty.");
+                    return Ok(());
+                }
+                for (i, quest) in notices.iter().enumerate() {
+                    println!("{}. {}", i + 1, quest.name);
+                    println!("   {}", quest.description);
+                }
+                println!("accept <number>");
+            },
+        }
+        return Ok(());
+    }
+
+    // picks up a whole stack from the ground, or everything with "all"
+    fn take_item(&mut self, name: &str) -> Result<(), PlayerError> {
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        let taken = self.world.items_at_mut(x, y).take_named(name);
+        if taken.is_empty() && name == "all" {
+            return Err(PlayerError::NothingHere);
+        }
+        if taken.is_empty() {
+            return Err(PlayerError::NotOnGround);
+        }
+
+        for item in taken {
+            println!("You pick up {}.", item.label());
+            self.inventory.add(item);
+        }
+        return Ok(());
+    }
+
+    // the contents of a held container or of the open chest here, with the weight they can hold
+    fn container_mut(&mut self, name: &str) -> Result<(&mut Inventory, f32), PlayerError> {
+        if let Some(index) = self.inventory.find(name) {
+            let item = self.inventory.get_mut(index);
+            let capacity = item.def().capacity;
+            match (item.contents.as_mut(), capacity) {
+                (Some(contents), Some(capacity)) => return Ok((contents, capacity)),
+                _ => return Err(PlayerError::NotAContainer),
+            }
+        }
+
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        match self.world.chest_at_mut(x, y) {
+            Some(chest) if chest.matches(name) => {
+                if !chest.open {
+                    return Err(PlayerError::Closed);
+                }
+                return Ok((&mut chest.items, chest.capacity));
+            },
+            _ => return Err(PlayerError::NoContainer),
+        }
+    }
+
+    fn take_from(&mut self, name: &str, source: &str) -> Result<(), PlayerError> {
+        let (contents, _) = self.container_mut(source)?;
+        let taken = contents.take_named(name);
+        if taken.is_empty() && name == "all" {
+            return Err(PlayerError::Empty);
+        }
+        if taken.is_empty() {
+            return Err(PlayerError::NotInContainer);
+        }
+
+        for item in taken {
+            println!("You take {} from the {}.", item.label(), source.to_lowercase());
+            self.inventory.add(item);
+        }
+        return Ok(());
+    }
+
+    fn put_item(&mut self, name: &str, target: &str) -> Result<(), PlayerError> {
+        let index = match self.inventory.find(name) {
+            Some(index) => index,
+            None => return Err(PlayerError::ItemNotHeld),
+        };
+
+        // bags hold loose items only, so saves never need more than one level of nesting
+        if self.inventory.get(index).contents.is_some() && self.inventory.find(target).is_some() {
+            return Err(PlayerError::ContainerFull);
+        }
+
+        // taken out first so a container can never end up inside itself
+        let item = self.inventory.take(index, i32::MAX);
+        let err = match self.container_mut(target) {
+            Ok((contents, capacity)) if contents.fits(&item, capacity) => {
+                println!("You put {} in the {}.", item.label(), target.to_lowercase());
+                contents.add(item);
+                return Ok(());
+            },
+            Ok(_) => PlayerError::ContainerFull,
+            Err(err) => err,
+        };
+
+        self.inventory.add(item);
+        return Err(err);
+    }
+
+    fn open(&mut self, name: &str) -> Result<(), PlayerError> {
+        if let Some(index) = self.inventory.find(name) {
+            let item = self.inventory.get(index);
+            match &item.contents {
+                Some(contents) if contents.is_empty() => println!("The {} is empty.", item.name().to_lowercase()),
+                Some(contents) => println!("The {} holds: {}", item.name().to_lowercase(), contents.summary()),
+                None => return Err(PlayerError::NotAContainer),
+            }
+            return Ok(());
+        }
+
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        let chest = match self.world.chest_at_mut(x, y) {
+            Some(chest) if chest.matches(name) => chest,
+            _ => return Err(PlayerError::NoContainer),
+        };
+
+        if chest.locked {
+            let key = chest.key.clone().unwrap();
+            if self.inventory.count(&key) == 0 {
+                return Err(PlayerError::Locked);
+            }
+            chest.locked = false;
+            println!("You unlock the {} with the {}.", chest.name.to_lowercase(), item::name_of(&key));
+        }
+
+        chest.open = true;
+        if chest.items.is_empty() {
+            println!("The {} is empty.", chest.name.to_lowercase());
+        } else {
+            println!("Inside the {}: {}", chest.name.to_lowercase(), chest.items.summary());
+        }
+        return Ok(());
+    }
+
+    fn close(&mut self, name: &str) -> Result<(), PlayerError> {
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        match self.world.chest_at_mut(x, y) {
+            Some(chest) if chest.matches(name) => {
+                chest.open = false;
+                println!("You close the {}.", chest.name.to_lowercase());
+                return Ok(());
+            },
+            _ => return Err(PlayerError::NoContainer),
+        }
+    }
+
+    fn drop_item(&mut self, name: &str) -> Result<(), PlayerError> {
+        let index = match self.inventory.find(name) {
+            Some(index) => index,
+            None => return Err(PlayerError::ItemNotHeld),
+        };
+
+        let item = self.inventory.take(index, i32::MAX);
+        println!("You drop {}.", item.label());
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        self.world.items_at_mut(x, y).add(item);
+        return Ok(());
+    }
+
+    fn has_materials(&self, recipe: &Recipe) -> bool {
+        return recipe.has_materials(&self.inventory);
+    }
+
+    fn has_tool(&self, id: &str) -> bool {
+        return self.inventory.count(id) > 0 || self.equipment.has(id);
+    }
+
+    fn at_station(&self, station: &Station) -> bool {
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        match station {
+            Station::Service(service) => {
+                return self.in_town && self.world.town_at(x, y).is_some_and(|t| t.has_service(*service));
+            },
+            Station::Terrain(tile) => return self.world.tile_name(x, y) == *tile,
+            Station::Campfire => return self.world.has_campfire(x, y),
+        }
+    }
+
+    // recipes the player has the materials for, with whatever else they still need
+    fn display_recipes(&self) {
+        let known: Vec<&Recipe> = crafting::recipes().iter().filter(|r| self.has_materials(r)).collect();
+        if known.is_empty() {
+            println!("You don't have the materials to craft anything.");
+            return;
+        }
+
+        for recipe in known {
+            let (_, count) = recipe.output;
+            if count > 1 {
+                print!("{} x{}", recipe.name(), count);
+            } else {
+                print!("{}", recipe.name());
+            }
+            execute!(stdout(), cursor::MoveToColumn(18)).expect("failed to execute");
+            print!("{}", recipe.describe_inputs());
+            execute!(stdout(), cursor::MoveToColumn(42)).expect("failed to execute");
+
+            let mut needs: Vec<String> = recipe.tools.iter()
+                .filter(|id| !self.has_tool(id))
+                .map(|id| item::name_of(id).to_string())
+                .collect();
+            if let Some(station) = recipe.station.as_ref().filter(|s| !self.at_station(s)) {
+                needs.push(station.to_string());
+            }
+            if needs.is_empty() {
+                println!("ready");
+            } else {
+                println!("needs {}", needs.join(", "));
+            }
+        }
+        println!("craft <item>");
+    }
+
+    fn craft(&mut self, name: &str) -> Result<(), PlayerError> {
+        let recipe = match Item::lookup(name).and_then(|item| crafting::recipes().iter().find(|r| r.output.0 == item.id)) {
+            Some(recipe) => recipe,
+            None => return Err(PlayerError::UnknownRecipe),
+        };
+
+        if !self.has_materials(recipe) {
+            return Err(PlayerError::MissingMaterials);
+        }
+        if !recipe.tools.iter().all(|id| self.has_tool(id)) {
+            return Err(PlayerError::MissingTool);
+        }
+        if recipe.station.as_ref().is_some_and(|s| !self.at_station(s)) {
+            return Err(PlayerError::WrongPlace);
+        }
+
+        recipe.use_materials(&mut self.inventory);
+        let (id, count) = &recipe.output;
+        let mut item = Item::new(id).unwrap();
+        item.count = *count;
+        println!("You craft {}.", item.label());
+        self.inventory.add(item);
+        self.pass_time(CRAFT_TURNS);
+        return Ok(());
+    }
+
+    // works the tile underfoot or a neighbouring one, so rivers and mountains can be used from beside them
+    fn harvest(&mut self, action: Action) -> Result<(), PlayerError> {
+        if self.in_town {
+            return Err(PlayerError::InTown);
+        }
+
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        let spots = [(x, y), (x, y - 1), (x + 1, y), (x, y + 1), (x - 1, y)];
+        let found: Vec<_> = spots.iter()
+            .filter_map(|&(x, y)| resource::find(action, &self.world.tile_name(x, y)).map(|r| (x, y, r)))
+            .collect();
+        if found.is_empty() {
+            return Err(PlayerError::NothingToHarvest);
+        }
+
+        let (x, y, resource) = match found.iter().find(|(x, y, r)| self.world.resource_left(*x, *y, r) > 0) {
+            Some(&spot) => spot,
+            None => return Err(PlayerError::Depleted),
+        };
+        if resource.tool.as_ref().is_some_and(|tool| !self.has_tool(tool)) {
+            return Err(PlayerError::MissingTool);
+        }
+
+        let seed = Rng::seed_from(&resource.id) ^ self.world.turn() ^ ((x as u64) << 32 | y as u64);
+        let mut rng = Rng::new(seed);
+        let mut found = vec!();
+        for (id, low, high) in resource.yields.iter() {
+            let count = rng.range(*low, *high);
+            if count > 0 {
+                let mut item = Item::new(id).unwrap();
+                item.count = count;
+                found.push(item);
+            }
+        }
+
+        // a practised forager turns up a little extra of everything
+        if action == Action::Gather {
+            let chance = FORAGING_BONUS * self.skills.level(Skill::Foraging) as i32;
+            for item in found.iter_mut() {
+                if self.rng.range(1, 100) <= chance {
+                    item.count += 1;
+                }
+            }
+        }
+
+        println!("You {} for a while.", action);
+        if found.is_empty() {
+            println!("You come away with nothing.");
+        } else {
+            let names: Vec<String> = found.iter().map(|item| item.label()).collect();
+            println!("You get {}.", names.join(", "));
+        }
+        for item in found {
+            self.inventory.add(item);
+        }
+
+        if action == Action::Gather {
+            self.practice(Skill::Foraging);
+        }
+        self.world.harvest(x, y, resource);
+        self.pass_time(1);
+        if self.world.resource_left(x, y, resource) > 0 {
+            return Ok(());
+        }
+
+        // felling the last of the timber clears the land for good
+        match (action, self.world.tile_name(x, y)) {
+            (Action::Chop, TileName::Forest) => {
+                println!("The forest thins out into scattered trees.");
+                self.world.set_tile(x, y, TileName::Trees);
+            },
+            (Action::Chop, TileName::Trees) => {
+                println!("The last of the trees are gone, leaving open grass.");
+                self.world.set_tile(x, y, TileName::Plains);
+            },
+            _ => println!("There is nothing left here for now."),
+        }
+        return Ok(());
+    }
+
+    fn build(&mut self, what: &str) -> Result<(), PlayerError> {
+        if self.in_town {
+            return Err(PlayerError::InTown);
+        }
+
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        let (ahead_x, ahead_y) = self.facing_tile();
+        let (ahead_x, ahead_y) = (ahead_x as usize, ahead_y as usize);
+        let cost: &[(&str, i32)] = match what {
+            "bridge" if self.world.tile_name(ahead_x, ahead_y) == TileName::Water => &BRIDGE_COST,
+            "campfire" | "fire" if !self.world.has_campfire(x, y) && self.world.is_accessible(x, y) => &CAMPFIRE_COST,
+            "bridge" | "campfire" | "fire" => return Err(PlayerError::CantBuildHere),
+            _ => return Err(PlayerError::UnknownStructure),
+        };
+
+        if cost.iter().any(|(id, count)| self.inventory.count(id) < *count) {
+            return Err(PlayerError::MissingMaterials);
+        }
+        for (id, count) in cost {
+            self.inventory.remove(id, *count);
+        }
+
+        if what == "bridge" {
+            self.world.set_tile(ahead_x, ahead_y, TileName::Bridge);
+            println!("You lash logs together into a bridge across the river.");
+        } else {
+            self.world.add_campfire(x, y);
+            println!("You build a campfire and it crackles to life.");
+        }
+        self.pass_time(2);
+        self.dry_off();
+        return Ok(());
+    }
+
+    fn buy(&mut self, item_name: &str) -> Result<(), PlayerError> {
+        if !self.in_town {
+            return Err(PlayerError::NotInTown);
+        }
+
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        let town = self.world.town_at(x, y).unwrap();
+        if !town.has_service(Service::Shop) {
+            return Err(PlayerError::NoService);
+        }
+
+        let ware = town.wares().iter()
+            .map(|id| Item::new(id).unwrap())
+            .find(|item| item.matches(item_name));
+        let item = match ware {
+            Some(item) => item,
+            None => return Err(PlayerError::UnknownItem),
+        };
+        let price = item.def().value;
+
+        if self.gold < price {
+            return Err(PlayerError::NotEnoughGold);
+        }
+
+        self.gold -= price;
+        println!("You buy a {} for {} gold.", item.name(), price);
+        self.inventory.add(item);
+        return Ok(());
+    }
+
+    fn accept_quest(&mut self, number: &str) -> Result<(), PlayerError> {
+        if !self.in_town {
+            return Err(PlayerError::NotInTown);
+        }
+
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        let town = self.world.town_at_mut(x, y).unwrap();
+        if !town.has_service(Service::NoticeBoard) {
+            return Err(PlayerError::NoService);
+        }
+
+        let index = match number.parse::<usize>() {
+            Ok(n) if n > 0 => n - 1,
+            _ => return Err(PlayerError::UnknownQuest),
+        };
+
+        let posted = town.quests.iter()
+            .enumerate()
+            .filter(|(_, q)| q.unlocked)
+            .map(|(i, _)| i)
+            .nth(index);
+
+        match posted {
+            Some(i) => {
+                let quest = town.quests.remove(i);
+                self.take_on_quest(quest);
+                return Ok(());
+            },
+            None => return Err(PlayerError::UnknownQuest),
+        }
+    }
+
+    fn current_town(&self) -> Option<String> {
+        if !self.in_town {
+            return None;
+        }
+        return self.world.town_at(self.pos.0 as usize, self.pos.1 as usize).map(|t| t.name.clone());
+    }
+
+    fn talk(&mut self, name: &str) -> Result<(), PlayerError> {
+        let town = self.current_town();
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        let npc = self.world.npcs_at(x, y, town.as_deref()).into_iter()
+            .find(|n| n.id.eq_ignore_ascii_case(name) || n.name.eq_ignore_ascii_case(name))
+            .cloned();
+
+        match npc {
+            Some(npc) => {
+                self.enter_node(npc.name, &npc.greeting);
+                return Ok(());
+            },
+            None => return Err(PlayerError::UnknownNpc),
+        }
+    }
+
+    fn enter_node(&mut self, npc: String, node_id: &str) {
+        let node = self.world.dialogue_node(node_id).clone();
+        println!("{}: {}", npc, node.text);
+        for effect in node.effects.iter() {
+            self.apply_effect(effect);
+        }
+
+        let choices = node.available(&self.inventory, &self.journal, self.reputation);
+        if choices.is_empty() {
+            self.end_conversation(&npc);
+            return;
+        }
+        for (i, choice) in choices.iter().enumerate() {
+            println!("{}. {}", i + 1, choice.text);
+        }
+        self.conversation = Some((npc, node_id.to_string()));
+    }
+
+    fn apply_effect(&mut self, effect: &Effect) {
+        match effect {
+            Effect::GiveItem(id) => {
+                let item = Item::new(id).unwrap();
+                println!("You receive {}.", item.name());
+                self.inventory.add(item);
+            },
+            Effect::TakeItem(id) => {
+                if self.inventory.remove(id, 1) {
+                    println!("You hand over {}.", item::name_of(id));
+                }
+            },
+            Effect::Gold(amount) => {
+                self.gold = self.gold.saturating_add_signed(*amount);
+                if *amount < 0 {
+                    println!("You pay {} gold.", -amount);
+                } else {
+                    println!("You receive {} gold.", amount);
+                }
+            },
+            Effect::Reputation(amount) => self.reputation += amount,
+            Effect::StartQuest(id) => {
+                let Some(quest) = self.world.take_quest(id) else {
+                    return;
+                };
+                // a conversation can't skip ahead of the quests this one follows on from
+                if !quest.prerequisites.iter().all(|id| self.journal.is_completed(id)) {
+                    println!("You are not ready for that task yet.");
+                    self.world.post_quests(vec!(quest));
+                    return;
+                }
+                self.take_on_quest(quest);
+            },
+            Effect::Status(status, turns) => self.add_status(*status, *turns),
+            Effect::Forecast => self.forecast(),
+        }
+    }
+
+    // a conversation only counts towards quests once it is over
+    fn end_conversation(&mut self, npc: &str) {
+        self.conversation = None;
+        self.journal.record_talk(npc);
+    }
+
+    // handles input while a conversation is open: a numbered reply or `bye`
+    fn converse(&mut self, input: &str) -> Result<(), PlayerError> {
+        let (npc, node_id) = self.conversation.clone().unwrap();
+        if input == "bye" {
+            println!("You take your leave of {}.", npc);
+            self.end_conversation(&npc);
+            return Ok(());
+        }
+
+        let index = match input.parse::<usize>() {
+            Ok(n) if n > 0 => n - 1,
+            Ok(_) => return Err(PlayerError::InvalidChoice),
+            Err(_) => return Err(PlayerError::InConversation),
+        };
+
+        let node = self.world.dialogue_node(&node_id).clone();
+        let choices = node.available(&self.inventory, &self.journal, self.reputation);
+        let choice = match choices.get(index) {
+            Some(choice) => choice,
+            None => return Err(PlayerError::InvalidChoice),
+        };
+
+        println!("> {}", choice.text);
+        match &choice.next {
+            Some(next) => self.enter_node(npc, next),
+            None => self.end_conversation(&npc),
+        }
+        return Ok(());
+    }
+
+    pub fn check_command(&mut self, cmd: String) -> Result<(), PlayerError> {
+        self.run_command(cmd)?;
+        self.update_quests();
+        return Ok(());
+    }
+
+    // adds a quest to the journal along with anything handed over with it
+    fn take_on_quest(&mut self, quest: Quest) {
+        let given = quest.given.clone();
+        self.journal.accept(quest);
+        for id in given {
+            let item = Item::new(&id).unwrap();
+            println!("You are handed {}.", item.name());
+            self.inventory.add(item);
+        }
+    }
+
+    fn update_quests(&mut self) {
+        let (x, y) = (self.pos.0 as usize, self.pos.1 as usize);
+        let town = self.current_town();
+        for quest in self.world.fire_triggers((x, y), town.as_deref()) {
+            self.take_on_quest(quest);
+        }
+
+        let tile = self.world.tile_name(x, y);
+        let finished = self.journal.update((x, y), &tile, &mut self.inventory);
+        if finished.is_empty() {
+            return;
+        }
+
+        for quest in finished {
+            println!("Quest complete: {}", quest.name);
+            for reward in quest.rewards {
+                println!("You receive {}.", reward);
+                match reward {
+                    Reward::Gold(amount) => self.gold += amount,
+                    Reward::Item(id) => {
+                        self.inventory.add(Item::new(&id).unwrap());
+                    },
+                    Reward::Experience(amount) => self.gain_xp(amount),
+                }
+            }
+            self.gain_xp(QUEST_XP);
+        }
+
+        let journal = &self.journal;
+        self.world.unlock_quests(|id| journal.is_completed(id));
+    }
+
+    fn run_command(&mut self, cmd: String) -> Result<(), PlayerError> {
+        if self.conversation.is_some() {
+            return self.converse(cmd.trim());
+        }
+        if self.combat.is_some() {
+            return self.fight(cmd.trim());
+        }
+
+        if cmd.len() < 4 {
+            return Err(PlayerError::InvalidCommandLength);
+        }
+        let mut cmd_args = cmd.split_whitespace();
+    
+        let base_cmd = cmd_args.next().unwrap();
+    
+        match base_cmd {
+            "help" => {
+                println!("Avalible commands:\nmove <forward/backward/left/right>\nlook <forward/backward/left/right>\nuse <item>\nexamine <item>\ninventory\nstatus\nenter\nleave\nvisit <inn/shop/forge/board>\nbuy <item>\naccept <number>\njournal\ntalk to <npc>\ntake <item/all>\ndrop <item>\nopen <container>\nclose <container>\nput <item> in <container>\ntake <item/all> from <container>\nequip <item>\nunequip <item/slot>\nrecipes\ncraft <item>\ngather\nchop\nfish\nmine\nbuild <bridge/campfire>\ndrink\nrest\ncamp\nsleep\ntrain <attribute>\ndifficulty [level]\nsurvival [on/off]\nsave\nIn a fight: attack, defend, flee, use <item>, log");
+                Ok(())
+            },
+            "inventory" => {
+                self.display_inventory();
+                Ok(())
+            },
+            "status" => {
+                self.display_status();
+                Ok(())
+            },
+            "enter" => self.enter_town(),
+            "leave" => self.leave_town(),
+            "visit" => {
+                let place = cmd_args.next();
+                if place.is_none() {return Err(PlayerError::InvalidCommandLength)}
+                self.visit(place.unwrap())
+            },
+            "accept" => {
+                let number = cmd_args.next();
+                if number.is_none() {return Err(PlayerError::InvalidCommandLength)}
+                self.accept_quest(number.unwrap())
+            },
+            "talk" => {
+                let mut name: Vec<&str> = cmd_args.collect();
+                if name.first() == Some(&"to") {
+                    name.remove(0);
+                }
+                if name.is_empty() {return Err(PlayerError::InvalidCommandLength)}
+                self.talk(&name.join(" "))
+            },
+            "journal" => {
+                self.journal.display(&self.inventory);
+                Ok(())
+            },
+            "buy" => {
+                let item: Vec<&str> = cmd_args.collect();
+                if item.is_empty() {return Err(PlayerError::InvalidCommandLength)}
+                self.buy(&item.join(" "))
+            },
+            "use" => {
+                let item: Vec<&str> = cmd_args.collect();
+                if item.is_empty() {return Err(PlayerError::InvalidCommandLength)}
+                self.use_item(&item.join(" "))
+            },
+            "take" => {
+                let item: Vec<&str> = cmd_args.collect();
+                if item.is_empty() {return Err(PlayerError::InvalidCommandLength)}
+                match item.join(" ").split_once(" from ") {
+                    Some((item, source)) => self.take_from(item, source),
+                    None => self.take_item(&item.join(" ")),
+                }
+            },
+            "put" => {
+                let args: Vec<&str> = cmd_args.collect();
+                match args.join(" ").split_once(" in ") {
+                    Some((item, target)) => self.put_item(item, target),
+                    None => Err(PlayerError::InvalidCommandLength),
+                }
+            },
+            "gather" | "forage" | "chop" | "fish" | "mine" => {
+                self.harvest(Action::parse(base_cmd).unwrap())
+            },
+            "build" => {
+                let what = cmd_args.next();
+                if what.is_none() {return Err(PlayerError::InvalidCommandLength)}
+                self.build(what.unwrap())
+            },
+            "train" => {
+                let attribute = cmd_args.next();
+                if attribute.is_none() {return Err(PlayerError::InvalidCommandLength)}
+                self.train(attribute.unwrap())
+            },
+            "difficulty" => self.set_difficulty(cmd_args.next()),
+            "survival" => self.set_survival(cmd_args.next()),
+            "drink" => self.drink(),
+            "rest" | "camp" | "sleep" => self.rest(base_cmd),
+            "save" => {
+                if save::write(self).is_err() {
+                    return Err(PlayerError::SaveFailed);
+                }
+                println!("Game saved.");
+                Ok(())
+            },
+            "recipes" => {
+                self.display_recipes();
+                Ok(())
+            },
+            "craft" => {
+                let item: Vec<&str> = cmd_args.collect();
+                if item.is_empty() {return Err(PlayerError::InvalidCommandLength)}
+                self.craft(&item.join(" "))
+            },
+            "equip" => {
+                let item: Vec<&str> = cmd_args.collect();
+                if item.is_empty() {return Err(PlayerError::InvalidCommandLength)}
+                self.equip(&item.join(" "))
+            },
+            "unequip" => {
+                let item: Vec<&str> = cmd_args.collect();
+                if item.is_empty() {return Err(PlayerError::InvalidCommandLength)}
+                self.unequip(&item.join(" "))
+            },
+            "open" => {
+                let name: Vec<&str> = cmd_args.collect();
+                if name.is_empty() {return Err(PlayerError::InvalidCommandLength)}
+                self.open(&name.join(" "))
+            },
+            "close" => {
+                let name: Vec<&str> = cmd_args.collect();
+                if name.is_empty() {return Err(PlayerError::InvalidCommandLength)}
+                self.close(&name.join(" "))
+            },
+            "drop" => {
+                let item: Vec<&str> = cmd_args.collect();
+                if item.is_empty() {return Err(PlayerError::InvalidCommandLength)}
+                self.drop_item(&item.join(" "))
+            },
+            "examine" => {
+                let item: Vec<&str> = cmd_args.collect();
+                if item.is_empty() {return Err(PlayerError::InvalidCommandLength)}
+                self.examine(&item.join(" "))
+            },
+            "look" => {
+                let direction = cmd_args.next();
+                if direction.is_none() {return Err(PlayerError::InvalidCommandLength)}
+                let direction = direction.unwrap();
+    
+                if direction != "forward"
+                        && direction != "forwards"
+                        && direction != "up"
+                        && direction != "backward"
+                        && direction != "backwards"
+                        && direction != "down"
+                        && direction != "back"
+                        && direction != "left"
+                        && direction != "right" {
+                    return Err(PlayerError::InvalidMovement);
+                }
+                if self.rotate(direction).is_err() {
+                    return Err(PlayerError::InvalidMovement);
+                }
+                let (x, y) = self.facing_tile();
+                if !self.world.in_bounds(x, y) {
+                    return Err(PlayerError::OutOfBounds);
+                }
+                self.world.display_tile(x as usize, y as usize);
+                Ok(())
             },
             "move" => {
                 let direction = cmd_args.next();
@@ -272,4 +2027,153 @@ impl Player {
             _ => {return Err(PlayerError::UnknownCommand)},
         }
     }
-}
\ No newline at end of file
+}
+
+// reads a required value from a save section
+fn saved<T: FromStr>(section: &Section, key: &str) -> Result<T, ContentError> {
+    let entry = section.require(key)?;
+    return entry.value.parse().map_err(|_| ContentError::InvalidValue(entry.line, entry.value.clone()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a soldier with a compass, a map and two loaves of bread, on a seeded generator
+    fn soldier() -> Player {
+        character::load_test_classes();
+        bestiary::load_test_bestiary();
+        let class = character::find_class("soldier").unwrap();
+        let mut player = Player::new("Tester", class, class.attributes, false, World::test_map()).ok().unwrap();
+        player.rng = Rng::new(1);
+        return player;
+    }
+
+    #[test]
+    fn food_heals_and_is_eaten() {
+        let mut player = soldier();
+        player.health = 20;
+        assert!(player.use_item("bread").is_ok());
+        assert_eq!(player.health, 30);
+        assert_eq!(player.inventory.count("bread"), 1);
+        assert!(player.statuses.has(Status::WellFed));
+    }
+
+    #[test]
+    fn cures_only_what_the_player_has() {
+        let mut player = soldier();
+        player.inventory.add(Item::new("herbs").unwrap());
+        assert!(matches!(player.use_item("herbs"), Err(PlayerError::NothingToCure)));
+        assert_eq!(player.inventory.count("herbs"), 1);
+
+        player.add_status(Status::Poisoned, 5);
+        assert!(player.use_item("herbs").is_ok());
+        assert!(!player.statuses.has(Status::Poisoned));
+        assert_eq!(player.inventory.count("herbs"), 0);
+    }
+
+    #[test]
+    fn tools_are_kept() {
+        let mut player = soldier();
+        player.facing = 1;
+        assert!(player.use_item("compass").is_ok());
+        assert!(player.use_item("Compass").is_ok());
+        assert_eq!(player.inventory.count("compass"), 1);
+    }
+
+    #[test]
+    fn tools_with_durability_wear_out() {
+        let mut player = soldier();
+        let mut compass = player.inventory.take(player.inventory.find("compass").unwrap(), 1);
+        compass.durability = Some(2);
+        player.inventory.add(compass);
+
+        assert!(player.use_item("compass").is_ok());
+        let index = player.inventory.find("compass").unwrap();
+        assert_eq!(player.inventory.get(index).durability, Some(1));
+        assert!(player.use_item("compass").is_ok());
+        assert_eq!(player.inventory.count("compass"), 0);
+    }
+
+    #[test]
+    fn lights_are_held_up() {
+        let mut player = soldier();
+        player.inventory.add(Item::new("torch").unwrap());
+        assert!(player.use_item("torch").is_ok());
+        assert!(player.equipment.has("torch"));
+        assert_eq!(player.inventory.count("torch"), 0);
+    }
+
+    #[test]
+    fn only_held_items_with_an_effect_can_be_used() {
+        let mut player = soldier();
+        assert!(matches!(player.use_item("lantern"), Err(PlayerError::ItemNotHeld)));
+        player.inventory.add(Item::new("rope").unwrap());
+        assert!(matches!(player.use_item("rope"), Err(PlayerError::NotUsable)));
+    }
+
+    // forest east of Kingsreach, outside its patrols
+    const FOREST: (i32, i32) = (35, 19);
+    // turns from the start of the game until 22:00
+    const NIGHT: u64 = 14 * clock::TURNS_PER_HOUR;
+
+    // whether sleeping in the forest got cut short with this seed
+    fn woken(seed: u64, turn: u64, campfire: bool) -> bool {
+        let mut player = soldier();
+        player.rng = Rng::new(seed);
+        player.pos = FOREST;
+        player.world.advance(turn);
+        if campfire {
+            player.world.add_campfire(FOREST.0 as usize, FOREST.1 as usize);
+        }
+        assert!(player.rest("sleep").is_ok());
+        if player.combat.is_none() {
+            assert_eq!(player.world.turn(), turn + 60);
+            return false;
+        }
+        // only ever on a watch
+        assert_eq!((player.world.turn() - turn) % WATCH_TURNS, 0);
+        return true;
+    }
+
+    #[test]
+    fn nothing_attacks_a_daytime_rest() {
+        assert!((1..50).all(|seed| !woken(seed, 0, false)));
+    }
+
+    #[test]
+    fn night_rest_can_be_cut_short() {
+        let woken_plain = (1..200).filter(|seed| woken(*seed, NIGHT, false)).count();
+        let woken_campfire = (1..200).filter(|seed| woken(*seed, NIGHT, true)).count();
+        assert!(woken_plain > 0);
+        assert!(woken_campfire < woken_plain);
+    }
+
+    #[test]
+    fn resting_recovers_health() {
+        let mut player = soldier();
+        player.health = 10;
+        assert!(player.rest("rest").is_ok());
+        assert_eq!(player.health, 20);
+        assert_eq!(player.world.turn(), 10);
+    }
+
+    #[test]
+    fn towns_are_safe_but_beds_cost() {
+        let mut player = soldier();
+        // Kingsreach
+        player.pos = (20, 20);
+        player.world.advance(NIGHT);
+        assert!(player.enter_town().is_ok());
+        assert!(matches!(player.rest("camp"), Err(PlayerError::InTown)));
+
+        player.gold = INN_PRICE;
+        player.health = 10;
+        assert!(player.rest("sleep").is_ok());
+        assert_eq!((player.gold, player.health), (0, player.max_health()));
+        assert!(matches!(player.rest("sleep"), Err(PlayerError::NotEnoughGold)));
+
+        assert!(player.rest("rest").is_ok());
+        assert!(player.combat.is_none());
+    }
+}
@@ -0,0 +1,722 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+use crate::bestiary;
+use crate::content::{self, ContentError, Entry, Section, Writer};
+use crate::inventory::Inventory;
+use crate::item::{self, Item};
+use crate::questgen;
+use crate::town::Town;
+use crate::world::{TileName, World};
+
+#[derive(Clone, Debug)]
+pub enum Objective {
+    VisitTile { x: usize, y: usize },
+    ReachRegion(TileName),
+    CollectItems { item: String, count: i32 },
+    // like collecting, but the items are handed over once the stage is done
+    DeliverItems { item: String, count: i32 },
+    TalkTo(String),
+    Defeat { creature: String, count: i32 },
+}
+
+impl Display for Objective {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use Objective::*;
+        match self {
+            VisitTile { x, y } => write!(f, "Travel to {}, {}", x, y),
+            ReachRegion(tile) => write!(f, "Reach the {}", tile.label()),
+            CollectItems { item, count } => write!(f, "Collect {} {}", count, item::name_of(item)),
+            DeliverItems { item, count } => write!(f, "Hand over {} {}", count, item::name_of(item)),
+            TalkTo(npc) => write!(f, "Talk to {}", npc),
+            Defeat { creature, count } => write!(f, "Defeat {} {}", count, creature),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Reward {
+    Gold(u32),
+    Item(String),
+    Experience(u32),
+}
+
+impl Display for Reward {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use Reward::*;
+        match self {
+            Gold(amount) => write!(f, "{} gold", amount),
+            Item(id) => write!(f, "{}", item::name_of(id)),
+            Experience(amount) => write!(f, "{} experience", amount),
+        }
+    }
+}
+
+// how a quest reaches the player's journal
+#[derive(Clone, Debug)]
+pub enum Trigger {
+    Board(String),
+    EnterTown(String),
+    VisitTile { x: usize, y: usize },
+    ReachRegion(TileName),
+    // only started by an npc's dialogue
+    Dialogue,
+}
+
+#[derive(Clone, Debug)]
+pub struct Stage {
+    pub description: String,
+    pub objectives: Vec<Objective>,
+    // one counter per objective: 1 once a visit/reach/talk objective is met, kills so far for defeat
+    progress: Vec<i32>,
+}
+
+impl Stage {
+    pub fn new(description: &str) -> Stage {
+        return Stage {
+            description: description.to_string(),
+            objectives: vec!(),
+            progress: vec!(),
+        };
+    }
+
+    pub fn add_objective(&mut self, objective: Objective) {
+        self.objectives.push(objective);
+        self.progress.push(0);
+    }
+
+    // marks every objective the player currently satisfies by standing at `pos`
+    fn record_position(&mut self, pos: (usize, usize), tile: &TileName) {
+        for (objective, done) in self.objectives.iter().zip(self.progress.iter_mut()) {
+            match objective {
+                Objective::VisitTile { x, y } if (*x, *y) == pos => *done = 1,
+                Objective::ReachRegion(region) if region == tile => *done = 1,
+                _ => {},
+            }
+        }
+    }
+
+    fn record_talk(&mut self, npc: &str) {
+        for (objective, done) in self.objectives.iter().zip(self.progress.iter_mut()) {
+            if let Objective::TalkTo(name) = objective {
+                if name == npc {
+                    *done = 1;
+                }
+            }
+        }
+    }
+
+    // `names` are every way the creature may be referred to, e.g. "Wolf" and "wolves"
+    fn record_kill(&mut self, names: &[&str]) {
+        for (objective, kills) in self.objectives.iter().zip(self.progress.iter_mut()) {
+            if let Objective::Defeat { creature, .. } = objective {
+                if names.iter().any(|name| name.eq_ignore_ascii_case(creature)) {
+                    *kills += 1;
+                }
+            }
+        }
+    }
+
+    fn objective_done(&self, index: usize, inventory: &Inventory) -> bool {
+        match &self.objectives[index] {
+            Objective::CollectItems { item, count } | Objective::DeliverItems { item, count } => {
+                return inventory.count(item) >= *count;
+            },
+            Objective::Defeat { count, .. } => return self.progress[index] >= *count,
+            _ => return self.progress[index] > 0,
+        }
+    }
+
+    fn is_complete(&self, inventory: &Inventory) -> bool {
+        return (0..self.objectives.len()).all(|i| self.objective_done(i, inventory));
+    }
+
+    fn hand_over(&self, inventory: &mut Inventory) {
+        for objective in self.objectives.iter() {
+            if let Objective::DeliverItems { item, count } = objective {
+                inventory.remove(item, *count);
+                println!("You hand over {} {}.", count, item::name_of(item));
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Quest {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub trigger: Trigger,
+    pub stages: Vec<Stage>,
+    pub prerequisites: Vec<String>,
+    pub rewards: Vec<Reward>,
+    // items handed to the player along with the quest
+    pub given: Vec<String>,
+    pub unlocked: bool,
+    pub completed: bool,
+    stage: usize,
+}
+
+impl Quest {
+    pub fn new(id: &str, name: &str, description: &str, trigger: Trigger, stages: Vec<Stage>, rewards: Vec<Reward>) -> Quest {
+        return Quest {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+            trigger,
+            stages,
+            prerequisites: vec!(),
+            rewards,
+            given: vec!(),
+            unlocked: true,
+            completed: false,
+            stage: 0,
+        };
+    }
+
+    // the current stage and how far along its objectives are, e.g. `stage = 1` and `progress = 0 1 3`
+    fn save(&self, out: &mut Writer) {
+        out.entry("stage", self.stage);
+        let progress: Vec<String> = self.current_stage().progress.iter().map(|p| p.to_string()).collect();
+        if !progress.is_empty() {
+            out.entry("progress", progress.join(" "));
+        }
+    }
+
+    fn restore(&mut self, section: &Section) -> Result<(), ContentError> {
+        let entry = section.require("stage")?;
+        self.stage = match entry.value.parse::<usize>() {
+            Ok(stage) if stage < self.stages.len() => stage,
+            _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+        };
+
+        if let Some(entry) = section.get("progress") {
+            let progress: Vec<i32> = match entry.value.split_whitespace().map(|p| p.parse()).collect() {
+                Ok(progress) => progress,
+                Err(_) => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+            };
+            if progress.len() != self.stages[self.stage].progress.len() {
+                return Err(ContentError::InvalidValue(entry.line, entry.value.clone()));
+            }
+            self.stages[self.stage].progress = progress;
+        }
+        return Ok(());
+    }
+
+    fn current_stage(&self) -> &Stage {
+        return &self.stages[self.stage];
+    }
+
+    // advances through every stage that is already finished, returns true once the last one is
+    fn advance(&mut self, inventory: &mut Inventory) -> bool {
+        while self.stages[self.stage].is_complete(inventory) {
+            self.stages[self.stage].hand_over(inventory);
+            if self.stage + 1 == self.stages.len() {
+                return true;
+            }
+            self.stage += 1;
+            println!("{}: {}", self.name, self.current_stage().description);
+        }
+        return false;
+    }
+
+    pub fn print_progress(&self, inventory: &Inventory) {
+        let stage = self.current_stage();
+        println!("  {} ({}/{})", self.name, self.stage + 1, self.stages.len());
+        println!("    {}", stage.description);
+        for (i, objective) in stage.objectives.iter().enumerate() {
+            let mark = if stage.objective_done(i, inventory) { "x" } else { " " };
+            match objective {
+                Objective::CollectItems { item, count } | Objective::DeliverItems { item, count } => {
+                    let held = inventory.count(item).min(*count);
+                    println!("    [{}] {} ({}/{})", mark, objective, held, count);
+                },
+                Objective::Defeat { count, .. } => {
+                    let kills = stage.progress[i].min(*count);
+                    println!("    [{}] {} ({}/{})", mark, objective, kills, count);
+                },
+                _ => println!("    [{}] {}", mark, objective),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Journal {
+    pub active: Vec<Quest>,
+    pub completed: Vec<Quest>,
+}
+
+impl Journal {
+    pub fn accept(&mut self, quest: Quest) {
+        println!("New quest: {}", quest.name);
+        println!("{}", quest.description);
+        self.active.push(quest);
+    }
+
+    pub fn is_completed(&self, quest_id: &str) -> bool {
+        return self.completed.iter().any(|q| q.id == quest_id);
+    }
+
+    pub fn is_active(&self, quest_id: &str) -> bool {
+        return self.active.iter().any(|q| q.id == quest_id);
+    }
+
+    pub fn record_talk(&mut self, npc: &str) {
+        for quest in self.active.iter_mut() {
+            quest.stages[quest.stage].record_talk(npc);
+        }
+    }
+
+    pub fn record_kill(&mut self, names: &[&str]) {
+        for quest in self.active.iter_mut() {
+            quest.stages[quest.stage].record_kill(names);
+        }
+    }
+
+    // moves every finished quest to the completed list and returns copies of them
+    pub fn update(&mut self, pos: (usize, usize), tile: &TileName, inventory: &mut Inventory) -> Vec<Quest> {
+        let mut finished = vec!();
+        let mut active = vec!();
+        for mut quest in self.active.drain(..) {
+            quest.stages[quest.stage].record_position(pos, tile);
+            if quest.advance(inventory) {
+                quest.completed = true;
+                finished.push(quest);
+            } else {
+                active.push(quest);
+            }
+        }
+        self.active = active;
+
+        self.completed.extend(finished.iter().cloned());
+        return finished;
+    }
+
+    // one `quest` section per accepted quest
+    pub fn save(&self, out: &mut Writer) {
+        for (state, quests) in [("active", &self.active), ("completed", &self.completed)] {
+            for quest in quests.iter() {
+                out.section("quest", &quest.id);
+                out.entry("state", state);
+                quest.save(out);
+            }
+        }
+    }
+
+    // takes every saved quest back out of the world, where loading the content files put it
+    pub fn load(sections: &[Section], world: &mut World) -> Result<Journal, ContentError> {
+        let mut journal = Journal::default();
+        for section in sections.iter().filter(|s| s.kind == "quest") {
+            let mut quest = match world.take_quest(&section.id) {
+                Some(quest) => quest,
+                None => return Err(ContentError::UnknownQuest(section.line, section.id.clone())),
+            };
+            quest.restore(section)?;
+
+            let state = section.require("state")?;
+            match state.value.as_str() {
+                "active" => journal.active.push(quest),
+                "completed" => {
+                    quest.completed = true;
+                    journal.completed.push(quest);
+                },
+                _ => return Err(ContentError::InvalidValue(state.line, state.value.clone())),
+            }
+        }
+        return Ok(journal);
+    }
+
+    pub fn display(&self, inventory: &Inventory) {
+        println!("Active quests:");
+        if self.active.is_empty() {
+            println!("  none");
+        }
+        for quest in self.active.iter() {
+            quest.print_progress(inventory);
+        }
+
+        println!("Completed quests:");
+        if self.completed.is_empty() {
+            println!("  none");
+        }
+        for quest in self.completed.iter() {
+            println!("  {}", quest.name);
+        }
+    }
+}
+
+fn parse_tile(entry: &Entry, value: &str) -> Result<TileName, ContentError> {
+    match TileName::parse(value) {
+        Some(tile) => return Ok(tile),
+        None => return Err(ContentError::UnknownTile(entry.line, value.to_string())),
+    }
+}
+
+pub fn parse_coords(entry: &Entry, args: &[&str], world: &World) -> Result<(usize, usize), ContentError> {
+    let coords = match args {
+        [x, y] => (x.parse::<i32>(), y.parse::<i32>()),
+        _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+    };
+
+    match coords {
+        (Ok(x), Ok(y)) if world.in_bounds(x, y) && world.is_accessible(x as usize, y as usize) => {
+            return Ok((x as usize, y as usize));
+        },
+        _ => return Err(ContentError::UnknownTile(entry.line, args.join(" "))),
+    }
+}
+
+fn parse_town(entry: &Entry, name: &str) -> Result<String, ContentError> {
+    if !Town::exists(name) {
+        return Err(ContentError::UnknownTown(entry.line, name.to_string()));
+    }
+    return Ok(name.to_string());
+}
+
+fn parse_trigger(entry: &Entry, world: &World) -> Result<Trigger, ContentError> {
+    let args: Vec<&str> = entry.value.split_whitespace().collect();
+    match args.split_first() {
+        Some((&"board", town)) => return Ok(Trigger::Board(parse_town(entry, &town.join(" "))?)),
+        Some((&"enter", town)) => return Ok(Trigger::EnterTown(parse_town(entry, &town.join(" "))?)),
+        Some((&"visit", coords)) => {
+            let (x, y) = parse_coords(entry, coords, world)?;
+            return Ok(Trigger::VisitTile { x, y });
+        },
+        Some((&"reach", [tile])) => return Ok(Trigger::ReachRegion(parse_tile(entry, tile)?)),
+        Some((&"dialogue", [])) => return Ok(Trigger::Dialogue),
+        _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+    }
+}
+
+fn parse_objective(entry: &Entry, world: &World) -> Result<Objective, ContentError> {
+    let args: Vec<&str> = entry.value.split_whitespace().collect();
+    match args.split_first() {
+        Some((&"visit", coords)) => {
+            let (x, y) = parse_coords(entry, coords, world)?;
+            return Ok(Objective::VisitTile { x, y });
+        },
+        Some((&"reach", [tile])) => return Ok(Objective::ReachRegion(parse_tile(entry, tile)?)),
+        Some((&kind @ ("collect" | "deliver"), [item, count])) => {
+            let item = match Item::lookup(item) {
+                Some(item) => item.id,
+                None => return Err(ContentError::UnknownItem(entry.line, item.to_string())),
+            };
+            match count.parse() {
+                Ok(count) if count > 0 && kind == "collect" => return Ok(Objective::CollectItems { item, count }),
+                Ok(count) if count > 0 => return Ok(Objective::DeliverItems { item, count }),
+                _ => return Err(ContentError::InvalidValue(entry.line, count.to_string())),
+            }
+        },
+        Some((&"defeat", [count, creature @ ..])) if !creature.is_empty() => {
+            let creature = match bestiary::find(&creature.join(" ")) {
+                Some(creature) => creature.plural.clone(),
+                None => return Err(ContentError::UnknownCreature(entry.line, creature.join(" "))),
+            };
+            match count.parse() {
+                Ok(count) if count > 0 => return Ok(Objective::Defeat { creature, count }),
+                _ => return Err(ContentError::InvalidValue(entry.line, count.to_string())),
+            }
+        },
+        Some((&"talk", npc)) if !npc.is_empty() => match world.find_npc(&npc.join(" ")) {
+            Some(npc) => return Ok(Objective::TalkTo(npc.name.clone())),
+            None => return Err(ContentError::UnknownNpc(entry.line, npc.join(" "))),
+        },
+        _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+    }
+}
+
+fn parse_reward(entry: &Entry, kind: &str, value: &str) -> Result<Reward, ContentError> {
+    match kind {
+        "gold" => match value.parse() {
+            Ok(amount) => return Ok(Reward::Gold(amount)),
+            Err(_) => return Err(ContentError::InvalidValue(entry.line, value.to_string())),
+        },
+        "item" => match Item::lookup(value) {
+            Some(item) => return Ok(Reward::Item(item.id)),
+            None => return Err(ContentError::UnknownItem(entry.line, value.to_string())),
+        },
+        "xp" => match value.parse() {
+            Ok(amount) => return Ok(Reward::Experience(amount)),
+            Err(_) => return Err(ContentError::InvalidValue(entry.line, value.to_string())),
+        },
+        _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+    }
+}
+
+fn parse_reward_table(section: &Section, errors: &mut Vec<ContentError>) -> Vec<Reward> {
+    let mut rewards = vec!();
+    for entry in section.entries.iter() {
+        match parse_reward(entry, &entry.key, &entry.value) {
+            Ok(reward) => rewards.push(reward),
+            Err(err) => errors.push(err),
+        }
+    }
+    return rewards;
+}
+
+fn parse_quest(section: &Section, tables: &HashMap<String, Vec<Reward>>, world: &World, errors: &mut Vec<ContentError>) -> Option<Quest> {
+    let error_count = errors.len();
+
+    let name = section.require("name").map(|e| e.value.clone());
+    let description = section.require("description").map(|e| e.value.clone());
+    let trigger = section.require("trigger").and_then(|e| parse_trigger(e, world));
+
+    let mut stages: Vec<Stage> = vec!();
+    let mut rewards = vec!();
+    let mut given = vec!();
+    for entry in section.entries.iter() {
+        let result = match entry.key.as_str() {
+            "stage" => {
+                stages.push(Stage::new(&entry.value));
+                Ok(())
+            },
+            "objective" => parse_objective(entry, world).map(|objective| {
+                if stages.is_empty() {
+                    stages.push(Stage::new(description.as_deref().unwrap_or_default()));
+                }
+                stages.last_mut().unwrap().add_objective(objective);
+            }),
+            "reward" => match entry.value.split_once(' ') {
+                Some((kind, value)) => parse_reward(entry, kind, value.trim()).map(|r| rewards.push(r)),
+                None => Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+            },
+            "rewards" => match tables.get(&entry.value) {
+                Some(table) => {
+                    rewards.extend(table.iter().cloned());
+                    Ok(())
+                },
+                None => Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+            },
+            "give" => match Item::lookup(&entry.value) {
+                Some(item) => {
+                    given.push(item.id);
+                    Ok(())
+                },
+                None => Err(ContentError::UnknownItem(entry.line, entry.value.clone())),
+            },
+            "name" | "description" | "trigger" | "requires" => Ok(()),
+            _ => Err(ContentError::InvalidValue(entry.line, entry.key.clone())),
+        };
+
+        if let Err(err) = result {
+            errors.push(err);
+        }
+    }
+
+    let empty_stage = stages.is_empty() || stages.iter().any(|stage| stage.objectives.is_empty());
+    if empty_stage && errors.len() == error_count {
+        errors.push(ContentError::MissingKey(section.line, "objective".to_string()));
+    }
+
+    let (name, description, trigger) = match (name, description, trigger) {
+        (Ok(name), Ok(description), Ok(trigger)) => (name, description, trigger),
+        (name, description, trigger) => {
+            errors.extend([name.err(), description.err(), trigger.err()].into_iter().flatten());
+            return None;
+        },
+    };
+
+    if errors.len() > error_count {
+        return None;
+    }
+
+    let mut quest = Quest::new(&section.id, &name, &description, trigger, stages, rewards);
+    quest.prerequisites = section.all("requires").map(|e| e.value.clone()).collect();
+    quest.given = given;
+    quest.unlocked = quest.prerequisites.is_empty();
+    return Some(quest);
+}
+
+// whether following `requires` from a quest ever leads back to it
+fn requires_itself(id: &str, sections: &[Section]) -> bool {
+    let mut seen: Vec<&str> = vec!();
+    let mut queue = vec!(id);
+    while let Some(next) = queue.pop() {
+        let required = sections.iter()
+            .filter(|s| s.kind == "quest" && s.id == next)
+            .flat_map(|s| s.all("requires").map(|e| e.value.as_str()));
+        for required in required {
+            if required == id {
+                return true;
+            }
+            if !seen.contains(&required) {
+                seen.push(required);
+                queue.push(required);
+            }
+        }
+    }
+    return false;
+}
+
+// loads every quest in `path`, reporting all problems at once so writers can fix them in one pass
+pub fn load_quests(path: &str, world: &World) -> Result<Vec<Quest>, Vec<ContentError>> {
+    let sections = content::load(path).map_err(|err| vec!(err))?;
+    let mut errors = vec!();
+
+    let mut tables = HashMap::new();
+    for section in sections.iter().filter(|s| s.kind == "rewards") {
+        tables.insert(section.id.clone(), parse_reward_table(section, &mut errors));
+    }
+
+    let mut quests: Vec<Quest> = vec!();
+    for section in sections.iter() {
+        match section.kind.as_str() {
+            "quest" => {
+                // generated quests are named after their town, keep their ids free
+                if section.id.starts_with(questgen::ID_PREFIX) {
+                    errors.push(ContentError::InvalidValue(section.line, section.id.clone()));
+                    continue;
+                }
+                if quests.iter().any(|q| q.id == section.id) {
+                    errors.push(ContentError::Duplicate(section.line, section.id.clone()));
+                    continue;
+                }
+                if let Some(quest) = parse_quest(section, &tables, world, &mut errors) {
+                    quests.push(quest);
+                }
+            },
+            "rewards" => {},
+            _ => errors.push(ContentError::InvalidValue(section.line, section.kind.clone())),
+        }
+    }
+
+    for section in sections.iter().filter(|s| s.kind == "quest") {
+        for entry in section.all("requires") {
+            if !sections.iter().any(|s| s.kind == "quest" && s.id == entry.value) {
+                errors.push(ContentError::UnknownQuest(entry.line, entry.value.clone()));
+            }
+        }
+        if requires_itself(&section.id, &sections) {
+            errors.push(ContentError::RequiresItself(section.line, section.id.clone()));
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    return Ok(quests);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_one(src: &str) -> (Option<Quest>, Vec<ContentError>) {
+        item::load_test_catalogue();
+        let sections = content::parse(src).unwrap();
+        let mut errors = vec!();
+        let quest = parse_quest(&sections[0], &HashMap::new(), &World::test_map(), &mut errors);
+        return (quest, errors);
+    }
+
+    #[test]
+    fn parses_stages_objectives_and_rewards() {
+        let (quest, errors) = parse_one("[quest errand]\nname = Errand\ndescription = Run it.\ntrigger = board Emberfall\n\
+            requires = other\ngive = sealed_letter\nstage = Fetch.\nobjective = collect rope 2\nstage = Go.\nobjective = reach hills\n\
+            objective = deliver rope 2\nreward = gold 5\nreward = item bread\n");
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let quest = quest.unwrap();
+        assert_eq!(quest.stages.len(), 2);
+        assert_eq!(quest.stages[1].objectives.len(), 2);
+        assert!(matches!(quest.stages[1].objectives[1], Objective::DeliverItems { count: 2, .. }));
+        assert_eq!(quest.rewards.len(), 2);
+        assert_eq!(quest.given, vec!("sealed_letter".to_string()));
+        assert_eq!(quest.prerequisites, vec!("other".to_string()));
+        assert!(!quest.unlocked);
+    }
+
+    #[test]
+    fn objectives_before_any_stage_use_the_description() {
+        let (quest, _) = parse_one("[quest scout]\nname = Scout\ndescription = Look around.\ntrigger = dialogue\nobjective = reach forest\n");
+        let quest = quest.unwrap();
+        assert_eq!(quest.stages[0].description, "Look around.");
+        assert!(quest.unlocked);
+    }
+
+    #[test]
+    fn reports_every_problem() {
+        let (quest, errors) = parse_one("[quest broken]\ndescription = x\ntrigger = board Nowhere\ngive = anvil\nobjective = collect rope 0\n");
+        assert!(quest.is_none());
+        assert!(errors.contains(&ContentError::MissingKey(1, "name".to_string())));
+        assert!(errors.contains(&ContentError::UnknownTown(3, "Nowhere".to_string())));
+        assert!(errors.contains(&ContentError::UnknownItem(4, "anvil".to_string())));
+        assert!(errors.contains(&ContentError::InvalidValue(5, "0".to_string())));
+    }
+
+    #[test]
+    fn quests_need_an_objective() {
+        let (quest, errors) = parse_one("[quest idle]\nname = Idle\ndescription = x\ntrigger = dialogue\n");
+        assert!(quest.is_none());
+        assert_eq!(errors, vec!(ContentError::MissingKey(1, "objective".to_string())));
+    }
+
+    #[test]
+    fn finds_requires_cycles() {
+        let sections = content::parse("[quest a]\nrequires = c\n[quest b]\nrequires = a\n[quest c]\nrequires = b\n\
+            [quest d]\nrequires = d\n[quest e]\nrequires = a\nrequires = f\n[quest f]\n").unwrap();
+        for id in ["a", "b", "c", "d"] {
+            assert!(requires_itself(id, &sections), "{}", id);
+        }
+        // following on from a cycle doesn't put a quest in it
+        assert!(!requires_itself("e", &sections));
+        assert!(!requires_itself("f", &sections));
+    }
+
+    #[test]
+    fn journal_round_trips_through_saves() {
+        item::load_test_catalogue();
+        let bounty = || {
+            let mut first = Stage::new("Scout.");
+            first.add_objective(Objective::ReachRegion(TileName::Hills));
+            let mut second = Stage::new("Fetch.");
+            second.add_objective(Objective::VisitTile { x: 20, y: 20 });
+            second.add_objective(Objective::CollectItems { item: "rope".to_string(), count: 1 });
+            let scouting = Quest::new("scouting", "Scouting", "Go.", Trigger::Dialogue, vec!(first), vec!());
+            let errand = Quest::new("errand", "Errand", "Go.", Trigger::Dialogue, vec!(Stage::new("Wait."), second), vec!());
+            return vec!(scouting, errand);
+        };
+
+        let mut world = World::test_map();
+        world.post_quests(bounty());
+        let mut journal = Journal::default();
+        let mut scouting = world.take_quest("scouting").unwrap();
+        scouting.completed = true;
+        journal.completed.push(scouting);
+        let mut errand = world.take_quest("errand").unwrap();
+        errand.stage = 1;
+        errand.stages[1].record_position((20, 20), &TileName::Plains);
+        journal.active.push(errand);
+
+        let mut out = Writer::default();
+        journal.save(&mut out);
+        let sections = content::parse(&out.finish()).unwrap();
+
+        let mut world = World::test_map();
+        world.post_quests(bounty());
+        let loaded = Journal::load(&sections, &mut world).unwrap();
+        assert!(loaded.is_completed("scouting") && loaded.is_active("errand"));
+        assert_eq!(loaded.active[0].stage, 1);
+        assert_eq!(loaded.active[0].current_stage().progress, vec!(1, 0));
+        assert!(world.take_quest("errand").is_none());
+
+        // quests the content files no longer have can't be loaded
+        assert_eq!(Journal::load(&sections, &mut World::test_map()).unwrap_err(), ContentError::UnknownQuest(2, "errand".to_string()));
+    }
+
+    #[test]
+    fn delivered_items_are_handed_over() {
+        item::load_test_catalogue();
+        let mut stage = Stage::new("Bring rope.");
+        stage.add_objective(Objective::DeliverItems { item: "rope".to_string(), count: 2 });
+        let mut quest = Quest::new("rope", "Rope", "Bring rope.", Trigger::Dialogue, vec!(stage), vec!());
+
+        let mut inventory = Inventory::new(vec!(Item::new("rope").unwrap()));
+        assert!(!quest.advance(&mut inventory));
+        inventory.add(Item::new("rope").unwrap());
+        inventory.add(Item::new("rope").unwrap());
+        assert!(quest.advance(&mut inventory));
+        assert_eq!(inventory.count("rope"), 1);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::bestiary;
+use crate::item;
+use crate::quest::{Objective, Quest, Reward, Stage, Trigger};
+use crate::rng::Rng;
+use crate::town::{TownSize, TOWNS};
+use crate::world::{TileName, World};
+
+// every generated quest id starts with this so it can't clash with one from quests.txt
+pub const ID_PREFIX: &str = "gen_";
+
+// how far from town generated quests are allowed to send the player
+const SEARCH_RADIUS: i32 = 40;
+
+const DIRECTIONS: [(&str, i32, i32); 4] = [
+    ("north", 0, -1),
+    ("east", 1, 0),
+    ("south", 0, 1),
+    ("west", -1, 0),
+];
+
+// items a town may ask to be fetched
+const FETCH_ITEMS: [&str; 4] = ["bread", "rope", "torch", "bandage"];
+// what delivery quests have the player carry
+const LETTER: &str = "sealed_letter";
+
+// terrain worth charting, bounties go after whatever the bestiary has living there
+const WILDS: [TileName; 4] = [TileName::Forest, TileName::Trees, TileName::Hills, TileName::Plains];
+
+#[derive(Copy, Clone)]
+enum Kind {
+    Fetch,
+    Delivery,
+    Exploration,
+    Bounty,
+}
+
+fn kinds(size: TownSize) -> (usize, Vec<Kind>) {
+    use Kind::*;
+    match size {
+        TownSize::Capital => (4, vec!(Fetch, Delivery, Exploration, Bounty)),
+        TownSize::City => (3, vec!(Fetch, Delivery, Exploration, Bounty)),
+        TownSize::Village => (2, vec!(Fetch, Exploration, Bounty)),
+        TownSize::Hut => (1, vec!(Fetch, Exploration)),
+    }
+}
+
+// closest accessible tile of `terrain` roughly in the given direction from `from`
+fn find_terrain(world: &World, from: (usize, usize), dir: (i32, i32), terrain: &TileName) -> Option<(usize, usize)> {
+    let (fx, fy) = (from.0 as i32, from.1 as i32);
+    let mut best: Option<((usize, usize), i32)> = None;
+
+    for dy in -SEARCH_RADIUS..=SEARCH_RADIUS {
+        for dx in -SEARCH_RADIUS..=SEARCH_RADIUS {
+            // stay inside the quarter of the map facing `dir`
+            let forward = dx * dir.0 + dy * dir.1;
+            let sideways = (dx * dir.1 - dy * dir.0).abs();
+            if forward < 3 || sideways > forward {
+                continue;
+            }
+
+            let (x, y) = (fx + dx, fy + dy);
+            if !world.in_bounds(x, y) || !world.is_accessible(x as usize, y as usize) {
+                continue;
+            }
+            if world.tile_name(x as usize, y as usize) != *terrain {
+                continue;
+            }
+
+            let dist = dx.abs() + dy.abs();
+            if best.is_none_or(|(_, d)| dist < d) {
+                best = Some(((x as usize, y as usize), dist));
+            }
+        }
+    }
+
+    return best.map(|(pos, _)| pos);
+}
+
+// a board quest for the town, paying only gold
+fn board_quest(id: &str, town_name: &str, name: String, description: String, stages: Vec<Stage>, gold: u32) -> Quest {
+    return Quest::new(id, &name, &description, Trigger::Board(town_name.to_string()), stages, vec!(Reward::Gold(gold)));
+}
+
+fn generate_one(kind: Kind, rng: &mut Rng, world: &World, town: (usize, usize, &str), id: &str) -> Option<Quest> {
+    let (tx, ty, town_name) = town;
+    match kind {
+        Kind::Fetch => {
+            let item_id = *rng.pick(&FETCH_ITEMS);
+            let item = item::name_of(item_id);
+            let count = rng.range(2, 4);
+            let mut gather = Stage::new(&format!("Gather {} {}.", count, item));
+            gather.add_objective(Objective::CollectItems { item: item_id.to_string(), count });
+            let mut deliver = Stage::new(&format!("Bring the {} back to {}.", item, town_name));
+            deliver.add_objective(Objective::VisitTile { x: tx, y: ty });
+            deliver.add_objective(Objective::DeliverItems { item: item_id.to_string(), count });
+            let name = format!("Short on {}", item);
+            let desc = format!("{} is running low on {}. Gather {} and return.", town_name, item.to_lowercase(), count);
+            return Some(board_quest(id, town_name, name, desc, vec!(gather, deliver), 3 * count as u32));
+        },
+        Kind::Delivery => {
+            let others: Vec<_> = TOWNS.iter().filter(|(_, _, name, _)| *name != town_name).collect();
+            let (dx, dy, dest, _) = **rng.pick(&others);
+            let mut stage = Stage::new(&format!("Carry the sealed letter to {}.", dest));
+            stage.add_objective(Objective::VisitTile { x: dx, y: dy });
+            stage.add_objective(Objective::DeliverItems { item: LETTER.to_string(), count: 1 });
+            let distance = (tx.abs_diff(dx) + ty.abs_diff(dy)) as u32;
+            let name = format!("Letter for {}", dest);
+            let desc = format!("A merchant needs a letter carried from {} to {}.", town_name, dest);
+            let mut quest = board_quest(id, town_name, name, desc, vec!(stage), 5 + distance / 5);
+            quest.given.push(LETTER.to_string());
+            return Some(quest);
+        },
+        Kind::Exploration | Kind::Bounty => {
+            let (dir_name, dir_x, dir_y) = *rng.pick(&DIRECTIONS);
+            let terrain = rng.pick(&WILDS).clone();
+            let (x, y) = find_terrain(world, (tx, ty), (dir_x, dir_y), &terrain)?;
+
+            if let Kind::Exploration = kind {
+                let mut stage = Stage::new(&format!("Chart the {} to the {}.", terrain.label(), dir_name));
+                stage.add_objective(Objective::VisitTile { x, y });
+                let name = format!("Chart the {}", terrain.label());
+                let desc = format!("The cartographers of {} want the {} to the {} put on their maps.", town_name, terrain.label(), dir_name);
+                return Some(board_quest(id, town_name, name, desc, vec!(stage), 10));
+            }
+
+            let creatures = bestiary::habitat(&terrain);
+            if creatures.is_empty() {
+                return None;
+            }
+            let creature = &rng.pick(&creatures).plural;
+            let count = rng.range(2, 5);
+            let mut stage = Stage::new(&format!("Hunt down the {} in the {} to the {}.", creature, terrain.label(), dir_name));
+            stage.add_objective(Objective::VisitTile { x, y });
+            stage.add_objective(Objective::Defeat { creature: creature.clone(), count });
+            let name = format!("Bounty: {}", creature);
+            let desc = format!("Travellers near {} have been set upon by {}.", town_name, creature);
+            return Some(board_quest(id, town_name, name, desc, vec!(stage), 8 * count as u32));
+        },
+    }
+}
+
+// quests for a single town, always the same for the same town
+pub fn generate(world: &World, town: (usize, usize, &str, TownSize)) -> Vec<Quest> {
+    let (x, y, name, size) = town;
+    let mut rng = Rng::new(Rng::seed_from(name) ^ ((x as u64) << 32 | y as u64));
+    let (count, mut kinds) = kinds(size);
+
+    // each kind of quest is offered at most once per town
+    let mut quests = vec!();
+    while quests.len() < count && !kinds.is_empty() {
+        let kind = kinds.remove(rng.range(0, kinds.len() as i32 - 1) as usize);
+        let id = format!("{}{}_{}", ID_PREFIX, name.to_lowercase(), quests.len() + 1);
+        if let Some(quest) = generate_one(kind, &mut rng, world, (x, y, name), &id) {
+            quests.push(quest);
+        }
+    }
+    return quests;
+}
+
+pub fn generate_all(world: &World) -> Vec<Quest> {
+    return TOWNS.iter()
+        .flat_map(|&town| generate(world, town))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_world_gives_same_quests() {
+        bestiary::load_test_bestiary();
+        let world = World::test_map();
+        let first = generate_all(&world);
+        let second = generate_all(&world);
+
+        assert!(!first.is_empty());
+        assert_eq!(first.len(), second.len());
+        for (a, b) in first.iter().zip(second.iter()) {
+            assert_eq!((&a.id, &a.name, &a.description), (&b.id, &b.name, &b.description));
+        }
+    }
+
+    #[test]
+    fn quest_ids_are_unique_and_prefixed() {
+        bestiary::load_test_bestiary();
+        let quests = generate_all(&World::test_map());
+
+        for (i, quest) in quests.iter().enumerate() {
+            assert!(quest.id.starts_with(ID_PREFIX), "{}", quest.id);
+            assert!(quests[i + 1..].iter().all(|q| q.id != quest.id), "{} used twice", quest.id);
+        }
+    }
+}
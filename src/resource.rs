@@ -0,0 +1,247 @@
+use std::fmt::{self, Display, Formatter};
+use std::sync::OnceLock;
+
+use crate::content::{self, ContentError, Entry, Section};
+use crate::item::Item;
+use crate::world::TileName;
+
+// every gatherable resource, loaded once at startup by `load_resources`
+static RESOURCES: OnceLock<Vec<Resource>> = OnceLock::new();
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Action {
+    Gather,
+    Chop,
+    Fish,
+    Mine,
+}
+
+impl Action {
+    pub fn parse(value: &str) -> Option<Action> {
+        match value {
+            "gather" | "forage" => Some(Action::Gather),
+            "chop" => Some(Action::Chop),
+            "fish" => Some(Action::Fish),
+            "mine" => Some(Action::Mine),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use Action::*;
+        match self {
+            Gather => write!(f, "gather"),
+            Chop => write!(f, "chop"),
+            Fish => write!(f, "fish"),
+            Mine => write!(f, "mine"),
+        }
+    }
+}
+
+// what one action yields on one kind of tile
+#[derive(Clone, Debug)]
+pub struct Resource {
+    pub id: String,
+    pub action: Action,
+    pub tile: TileName,
+    // catalogue id with the lowest and highest amount found each time
+    pub yields: Vec<(String, i32, i32)>,
+    pub tool: Option<String>,
+    // times a single tile can be worked before it runs out
+    pub amount: u32,
+    // turns for one use to grow back
+    pub regrow: u64,
+}
+
+fn parse_yield(entry: &Entry) -> Result<(String, i32, i32), ContentError> {
+    let args: Vec<&str> = entry.value.split_whitespace().collect();
+    let (name, low, high) = match args.as_slice() {
+        [name, low, high] => (*name, *low, *high),
+        _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+    };
+
+    let id = match Item::lookup(name) {
+        Some(item) => item.id,
+        None => return Err(ContentError::UnknownItem(entry.line, name.to_string())),
+    };
+    match (low.parse::<i32>(), high.parse::<i32>()) {
+        (Ok(low), Ok(high)) if low >= 0 && high >= low && high > 0 => return Ok((id, low, high)),
+        _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+    }
+}
+
+// reads a required positive number
+fn number<T: std::str::FromStr + PartialOrd + Default>(section: &Section, key: &str) -> Result<T, ContentError> {
+    let entry = section.require(key)?;
+    match entry.value.parse::<T>() {
+        Ok(value) if value > T::default() => return Ok(value),
+        _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+    }
+}
+
+fn parse_resource(section: &Section, errors: &mut Vec<ContentError>) -> Option<Resource> {
+    let action = section.require("action").and_then(|entry| match Action::parse(&entry.value) {
+        Some(action) => Ok(action),
+        None => Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+    });
+    let tile = section.require("tile").and_then(|entry| match TileName::parse(&entry.value) {
+        Some(tile) => Ok(tile),
+        None => Err(ContentError::UnknownTile(entry.line, entry.value.clone())),
+    });
+    let tool = match section.get("tool") {
+        Some(entry) => match Item::lookup(&entry.value) {
+            Some(item) => Ok(Some(item.id)),
+            None => Err(ContentError::UnknownItem(entry.line, entry.value.clone())),
+        },
+        None => Ok(None),
+    };
+    let amount = number(section, "amount");
+    let regrow = number(section, "regrow");
+
+    let mut yields = vec!();
+    for entry in section.all("yield") {
+        match parse_yield(entry) {
+            Ok(found) => yields.push(found),
+            Err(err) => errors.push(err),
+        }
+    }
+    if section.get("yield").is_none() {
+        errors.push(ContentError::MissingKey(section.line, "yield".to_string()));
+    }
+
+    match (action, tile, tool, amount, regrow) {
+        (Ok(action), Ok(tile), Ok(tool), Ok(amount), Ok(regrow)) => return Some(Resource {
+            id: section.id.clone(),
+            action,
+            tile,
+            yields,
+            tool,
+            amount,
+            regrow,
+        }),
+        (action, tile, tool, amount, regrow) => {
+            errors.extend(action.err());
+            errors.extend(tile.err());
+            errors.extend(tool.err());
+            errors.extend(amount.err());
+            errors.extend(regrow.err());
+            return None;
+        },
+    }
+}
+
+// loads every resource, must run after the item catalogue
+pub fn load_resources(path: &str) -> Result<(), Vec<ContentError>> {
+    let sections = content::load(path).map_err(|err| vec!(err))?;
+    let mut errors = vec!();
+    let mut resources: Vec<Resource> = vec!();
+
+    for section in sections.iter() {
+        if section.kind != "resource" {
+            errors.push(ContentError::InvalidValue(section.line, section.kind.clone()));
+            continue;
+        }
+        if resources.iter().any(|r| r.id == section.id) {
+            errors.push(ContentError::Duplicate(section.line, section.id.clone()));
+            continue;
+        }
+        if let Some(resource) = parse_resource(section, &mut errors) {
+            resources.push(resource);
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    RESOURCES.set(resources).expect("resources loaded twice");
+    return Ok(());
+}
+
+// what `action` can find on a tile of the given kind
+pub fn find(action: Action, tile: &TileName) -> Option<&'static Resource> {
+    return RESOURCES.get().expect("resources not loaded").iter()
+        .find(|r| r.action == action && r.tile == *tile);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item;
+    use crate::world::World;
+
+    fn parse_one(text: &str) -> (Option<Resource>, Vec<ContentError>) {
+        item::load_test_catalogue();
+        let sections = content::parse(text).unwrap();
+        let mut errors = vec!();
+        let resource = parse_resource(&sections[0], &mut errors);
+        return (resource, errors);
+    }
+
+    fn ore() -> Resource {
+        return Resource {
+            id: "ore".to_string(),
+            action: Action::Mine,
+            tile: TileName::Mountain,
+            yields: vec!(("iron_ore".to_string(), 1, 2)),
+            tool: None,
+            amount: 2,
+            regrow: 10,
+        };
+    }
+
+    #[test]
+    fn parses_resource() {
+        let (resource, errors) = parse_one("[resource ore]\naction = mine\ntile = mountain\nyield = iron_ore 1 2\ntool = hammer\namount = 4\nregrow = 60\n");
+        assert!(errors.is_empty());
+        let resource = resource.unwrap();
+        assert_eq!(resource.action, Action::Mine);
+        assert_eq!(resource.tile, TileName::Mountain);
+        assert_eq!(resource.yields, vec!(("iron_ore".to_string(), 1, 2)));
+        assert_eq!(resource.tool, Some("hammer".to_string()));
+        assert_eq!((resource.amount, resource.regrow), (4, 60));
+    }
+
+    #[test]
+    fn reports_bad_resources() {
+        let (resource, errors) = parse_one("[resource ore]\naction = dig\ntile = moon\nyield = iron_ore 2 1\namount = 0\n");
+        assert!(resource.is_none());
+        assert_eq!(errors.len(), 5);
+        assert!(matches!(errors[0], ContentError::InvalidValue(4, _)));
+        assert!(errors.iter().any(|e| matches!(e, ContentError::UnknownTile(3, _))));
+        assert!(errors.iter().any(|e| matches!(e, ContentError::MissingKey(_, key) if key == "regrow")));
+    }
+
+    #[test]
+    fn shipped_resources_parse() {
+        item::load_test_catalogue();
+        let sections = content::load("./src/data/resources.txt").unwrap();
+        let mut errors = vec!();
+        for section in sections.iter() {
+            assert!(parse_resource(section, &mut errors).is_some());
+        }
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn tiles_run_out_and_grow_back() {
+        let mut world = World::default();
+        let ore = ore();
+        assert_eq!(world.resource_left(3, 3, &ore), 2);
+
+        world.harvest(3, 3, &ore);
+        world.harvest(3, 3, &ore);
+        world.harvest(3, 3, &ore);
+        assert_eq!(world.resource_left(3, 3, &ore), 0);
+        // other tiles are worked separately
+        assert_eq!(world.resource_left(4, 3, &ore), 2);
+
+        world.advance(9);
+        assert_eq!(world.resource_left(3, 3, &ore), 0);
+        world.advance(1);
+        assert_eq!(world.resource_left(3, 3, &ore), 1);
+        world.advance(100);
+        assert_eq!(world.resource_left(3, 3, &ore), 2);
+    }
+}
@@ -0,0 +1,48 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Small xorshift generator so seeded content stays reproducible across platforms.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        // xorshift never leaves an all zero state
+        return Rng { state: seed.max(1) };
+    }
+
+    // for anything that should differ from one game to the next
+    pub fn from_time() -> Rng {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(1, |d| d.as_nanos() as u64);
+        return Rng::new(nanos);
+    }
+
+    // FNV-1a, used to turn names into seeds
+    pub fn seed_from(text: &str) -> u64 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in text.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        return hash;
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        return self.state;
+    }
+
+    // uniform value in `low..=high`
+    pub fn range(&mut self, low: i32, high: i32) -> i32 {
+        let span = (high - low + 1) as u64;
+        return low + (self.next_u64() % span) as i32;
+    }
+
+    pub fn pick<'a, T>(&mut self, options: &'a [T]) -> &'a T {
+        let i = self.next_u64() % options.len() as u64;
+        return &options[i as usize];
+    }
+}
@@ -0,0 +1,35 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::content::{self, ContentError, Writer};
+use crate::player::Player;
+use crate::world::World;
+
+// Saves are written in the same section format as the content files, and only
+// record what differs from a world freshly built from the map and content files.
+pub const SAVE_PATH: &str = "./save.txt";
+
+pub fn exists() -> bool {
+    return Path::new(SAVE_PATH).exists();
+}
+
+pub fn write(player: &Player) -> io::Result<()> {
+    let mut out = Writer::default();
+    player.save(&mut out);
+    return fs::write(SAVE_PATH, out.finish());
+}
+
+// removes the save, used when a hardcore character dies
+pub fn delete() -> io::Result<()> {
+    if !exists() {
+        return Ok(());
+    }
+    return fs::remove_file(SAVE_PATH);
+}
+
+// `world` must be freshly built, the save is applied on top of it
+pub fn load(world: World) -> Result<Player, ContentError> {
+    let sections = content::load(SAVE_PATH)?;
+    return Player::load(world, &sections);
+}
@@ -0,0 +1,176 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::content::{ContentError, Section, Writer};
+
+// longest poison can build up to, however often the player is bitten
+const MAX_POISON: u32 = 30;
+
+// something affecting the player for a number of turns
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Status {
+    // loses health every turn, stacks
+    Poisoned,
+    // harder to dodge in wet clothes, dries off at a campfire
+    Soaked,
+    // moving takes longer and blows land softer
+    Exhausted,
+    // slowly regains health
+    WellFed,
+    // better armor and wards off poison
+    Blessed,
+}
+
+impl Status {
+    pub const ALL: [Status; 5] = [Status::Poisoned, Status::Soaked, Status::Exhausted, Status::WellFed, Status::Blessed];
+
+    // used in data files and saves
+    pub fn id(&self) -> &str {
+        use Status::*;
+        match self {
+            Poisoned => "poisoned",
+            Soaked => "soaked",
+            Exhausted => "exhausted",
+            WellFed => "well_fed",
+            Blessed => "blessed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Status> {
+        return Status::ALL.into_iter().find(|s| s.id() == value || s.to_string() == value);
+    }
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use Status::*;
+        match self {
+            Poisoned => write!(f, "poisoned"),
+            Soaked => write!(f, "soaked"),
+            Exhausted => write!(f, "exhausted"),
+            WellFed => write!(f, "well-fed"),
+            Blessed => write!(f, "blessed"),
+        }
+    }
+}
+
+// every status the player has and the turns left on each
+#[derive(Clone, Debug, Default)]
+pub struct Statuses {
+    active: Vec<(Status, u32)>,
+}
+
+impl Statuses {
+    pub fn has(&self, status: Status) -> bool {
+        return self.active.iter().any(|(s, _)| *s == status);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(Status, u32)> {
+        return self.active.iter();
+    }
+
+    // applies a status for `turns`, returns whether it took hold
+    pub fn add(&mut self, status: Status, turns: u32) -> bool {
+        if status == Status::Poisoned && self.has(Status::Blessed) {
+            return false;
+        }
+        if status == Status::Blessed {
+            self.remove(Status::Poisoned);
+        }
+
+        match self.active.iter_mut().find(|(s, _)| *s == status) {
+            // poison builds up, everything else just lasts as long as the longer of the two
+            Some((Status::Poisoned, left)) => *left = (*left + turns).min(MAX_POISON),
+            Some((_, left)) => *left = (*left).max(turns),
+            None if status == Status::Poisoned => self.active.push((status, turns.min(MAX_POISON))),
+            None => self.active.push((status, turns)),
+        }
+        return true;
+    }
+
+    // returns whether it was there to remove
+    pub fn remove(&mut self, status: Status) -> bool {
+        let count = self.active.len();
+        self.active.retain(|(s, _)| *s != status);
+        return self.active.len() < count;
+    }
+
+    // counts down one turn, returns the statuses that wore off
+    pub fn tick(&mut self) -> Vec<Status> {
+        for (_, left) in self.active.iter_mut() {
+            *left = left.saturating_sub(1);
+        }
+        let expired = self.active.iter().filter(|(_, left)| *left == 0).map(|(s, _)| *s).collect();
+        self.active.retain(|(_, left)| *left > 0);
+        return expired;
+    }
+
+    pub fn save(&self, out: &mut Writer) {
+        for (status, left) in self.active.iter() {
+            out.entry("status", format!("{} {}", status.id(), left));
+        }
+    }
+
+    pub fn load(section: &Section) -> Result<Statuses, ContentError> {
+        let mut statuses = Statuses::default();
+        for entry in section.all("status") {
+            match entry.value.split_once(' ').map(|(id, left)| (Status::parse(id), left.parse())) {
+                Some((Some(status), Ok(left))) if left > 0 => statuses.active.push((status, left)),
+                _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+            }
+        }
+        return Ok(statuses);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content;
+
+    #[test]
+    fn poison_builds_up_and_others_keep_the_longer_time() {
+        let mut statuses = Statuses::default();
+        statuses.add(Status::Poisoned, 20);
+        statuses.add(Status::Poisoned, 20);
+        statuses.add(Status::Soaked, 10);
+        statuses.add(Status::Soaked, 4);
+        assert_eq!(statuses.active, vec!((Status::Poisoned, MAX_POISON), (Status::Soaked, 10)));
+    }
+
+    #[test]
+    fn blessings_ward_off_poison() {
+        let mut statuses = Statuses::default();
+        statuses.add(Status::Poisoned, 5);
+        assert!(statuses.add(Status::Blessed, 5));
+        assert!(!statuses.has(Status::Poisoned));
+        assert!(!statuses.add(Status::Poisoned, 5));
+    }
+
+    #[test]
+    fn tick_wears_statuses_off() {
+        let mut statuses = Statuses::default();
+        statuses.add(Status::WellFed, 1);
+        statuses.add(Status::Exhausted, 2);
+        assert_eq!(statuses.tick(), vec!(Status::WellFed));
+        assert_eq!(statuses.tick(), vec!(Status::Exhausted));
+        assert_eq!(statuses.iter().count(), 0);
+    }
+
+    #[test]
+    fn round_trips_through_saves() {
+        let mut statuses = Statuses::default();
+        statuses.add(Status::WellFed, 12);
+        statuses.add(Status::Soaked, 3);
+
+        let mut out = Writer::default();
+        out.section("player", "save");
+        statuses.save(&mut out);
+        let loaded = Statuses::load(&content::parse(&out.finish()).unwrap()[0]).unwrap();
+        assert_eq!(loaded.active, statuses.active);
+
+        for value in ["cursed 3", "soaked 0", "soaked"] {
+            let sections = content::parse(&format!("[player save]\nstatus = {}\n", value)).unwrap();
+            assert_eq!(Statuses::load(&sections[0]).unwrap_err(), ContentError::InvalidValue(2, value.to_string()));
+        }
+    }
+}
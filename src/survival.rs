@@ -0,0 +1,141 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::content::{ContentError, Section, Writer};
+
+// a need at this level is as bad as it gets
+pub const MAX_NEED: u8 = 100;
+// levels at which each need starts to show and then to hurt
+const NOTICEABLE: u8 = 50;
+const SEVERE: u8 = 75;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Need {
+    Hunger,
+    Thirst,
+    Fatigue,
+}
+
+impl Need {
+    pub const ALL: [Need; 3] = [Need::Hunger, Need::Thirst, Need::Fatigue];
+
+    // how the player feels at each stage, from fine to as bad as it gets
+    fn feelings(&self) -> [&'static str; 4] {
+        use Need::*;
+        match self {
+            Hunger => ["fed", "hungry", "famished", "starving"],
+            Thirst => ["refreshed", "thirsty", "parched", "dehydrated"],
+            Fatigue => ["rested", "tired", "weary", "collapsing"],
+        }
+    }
+}
+
+impl Display for Need {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use Need::*;
+        match self {
+            Hunger => write!(f, "hunger"),
+            Thirst => write!(f, "thirst"),
+            Fatigue => write!(f, "fatigue"),
+        }
+    }
+}
+
+// how far each need has built up, 0 is fine and `MAX_NEED` the worst
+#[derive(Clone, Debug, Default)]
+pub struct Needs {
+    levels: [u8; 3],
+}
+
+impl Needs {
+    pub fn get(&self, need: Need) -> u8 {
+        return self.levels[need as usize];
+    }
+
+    // 0 fine, 1 noticeable, 2 severe, 3 maxed out
+    pub fn stage(&self, need: Need) -> usize {
+        match self.get(need) {
+            MAX_NEED => return 3,
+            level if level >= SEVERE => return 2,
+            level if level >= NOTICEABLE => return 1,
+            _ => return 0,
+        }
+    }
+
+    pub fn feeling(&self, need: Need) -> &'static str {
+        return need.feelings()[self.stage(need)];
+    }
+
+    // builds a need up, returns the new feeling when it got worse
+    pub fn add(&mut self, need: Need, amount: u8) -> Option<&'static str> {
+        let before = self.stage(need);
+        let level = &mut self.levels[need as usize];
+        *level = level.saturating_add(amount).min(MAX_NEED);
+        if self.stage(need) > before {
+            return Some(self.feeling(need));
+        }
+        return None;
+    }
+
+    pub fn relieve(&mut self, need: Need, amount: u8) {
+        let level = &mut self.levels[need as usize];
+        *level = level.saturating_sub(amount);
+    }
+
+    pub fn save(&self, out: &mut Writer) {
+        for need in Need::ALL {
+            out.entry(&need.to_string(), self.get(need));
+        }
+    }
+
+    // needs missing from the section start at 0
+    pub fn load(section: &Section) -> Result<Needs, ContentError> {
+        let mut needs = Needs::default();
+        for need in Need::ALL {
+            if let Some(entry) = section.get(&need.to_string()) {
+                match entry.value.parse() {
+                    Ok(level) if level <= MAX_NEED => needs.levels[need as usize] = level,
+                    _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+                }
+            }
+        }
+        return Ok(needs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content;
+
+    #[test]
+    fn needs_get_worse_in_stages() {
+        let mut needs = Needs::default();
+        assert_eq!(needs.add(Need::Hunger, NOTICEABLE - 1), None);
+        assert_eq!(needs.add(Need::Hunger, 1), Some("hungry"));
+        assert_eq!(needs.add(Need::Hunger, 200), Some("starving"));
+        assert_eq!(needs.get(Need::Hunger), MAX_NEED);
+
+        needs.relieve(Need::Hunger, 30);
+        assert_eq!(needs.feeling(Need::Hunger), "hungry");
+        needs.relieve(Need::Hunger, 200);
+        assert_eq!((needs.get(Need::Hunger), needs.feeling(Need::Thirst)), (0, "refreshed"));
+    }
+
+    #[test]
+    fn round_trips_through_saves() {
+        let mut needs = Needs::default();
+        needs.add(Need::Thirst, 60);
+        needs.add(Need::Fatigue, MAX_NEED);
+
+        let mut out = Writer::default();
+        out.section("player", "save");
+        needs.save(&mut out);
+        let loaded = Needs::load(&content::parse(&out.finish()).unwrap()[0]).unwrap();
+        assert_eq!(loaded.levels, [0, 60, MAX_NEED]);
+
+        // saves from before survival was added have no needs at all
+        assert_eq!(Needs::load(&content::parse("[player save]\n").unwrap()[0]).unwrap().levels, [0, 0, 0]);
+        let sections = content::parse("[player save]\nthirst = 101\n").unwrap();
+        assert_eq!(Needs::load(&sections[0]).unwrap_err(), ContentError::InvalidValue(2, "101".to_string()));
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::quest::Quest;
+
+// (x, y, name, size) of every town tile on the map
+pub const TOWNS: [(usize, usize, &str, TownSize); 5] = [
+    (20, 20, "Kingsreach", TownSize::Capital),
+    (167, 38, "Emberfall", TownSize::City),
+    (90, 30, "Millbrook", TownSize::Village),
+    (60, 6, "Westmere", TownSize::Village),
+    (140, 10, "Hillwatch", TownSize::Hut),
+];
+
+// catalogue ids of everything a shop can stock, sold at the item's value
+const WARES: [&str; 14] = [
+    "bread", "rope", "torch", "bandage", "hatchet", "fishing_rod", "pickaxe",
+    "lantern", "satchel", "hammer", "leather_cap", "jerkin", "dagger", "buckler",
+];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TownSize {
+    Capital,
+    City,
+    Village,
+    Hut,
+}
+
+impl TownSize {
+    pub fn services(&self) -> Vec<Service> {
+        use Service::*;
+        match self {
+            TownSize::Capital => vec!(Inn, Shop, Forge, NoticeBoard),
+            TownSize::City => vec!(Inn, Shop, Forge, NoticeBoard),
+            TownSize::Village => vec!(Inn, NoticeBoard),
+            TownSize::Hut => vec!(NoticeBoard),
+        }
+    }
+
+    // how many of the shop wares a town of this size keeps in stock
+    fn stock_size(&self) -> usize {
+        match self {
+            TownSize::Capital => WARES.len(),
+            TownSize::City => 7,
+            TownSize::Village => 2,
+            TownSize::Hut => 0,
+        }
+    }
+}
+
+impl Display for TownSize {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use TownSize::*;
+        match self {
+            Capital => write!(f, "capital"),
+            City => write!(f, "city"),
+            Village => write!(f, "village"),
+            Hut => write!(f, "hut"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Service {
+    Inn,
+    Shop,
+    Forge,
+    NoticeBoard,
+}
+
+impl Service {
+    pub fn parse(name: &str) -> Option<Service> {
+        match name {
+            "inn" => Some(Service::Inn),
+            "shop" => Some(Service::Shop),
+            "forge" | "smithy" => Some(Service::Forge),
+            "board" | "notice" | "noticeboard" => Some(Service::NoticeBoard),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Service {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use Service::*;
+        match self {
+            Inn => write!(f, "Inn"),
+            Shop => write!(f, "Shop"),
+            Forge => write!(f, "Forge"),
+            NoticeBoard => write!(f, "Notice Board"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Town {
+    pub name: String,
+    pub size: TownSize,
+    pub quests: Vec<Quest>,
+}
+
+impl Town {
+    pub fn new(name: &str, size: TownSize) -> Town {
+        return Town {
+            name: name.to_string(),
+            size,
+            quests: vec!(),
+        };
+    }
+
+    pub fn has_service(&self, service: Service) -> bool {
+        return self.size.services().contains(&service);
+    }
+
+    pub fn wares(&self) -> &[&'static str] {
+        return &WARES[..self.size.stock_size()];
+    }
+
+    pub fn exists(name: &str) -> bool {
+        return TOWNS.iter().any(|(_, _, town, _)| *town == name);
+    }
+
+    pub fn print_menu(&self) {
+        println!("Welcome to {}, a {}.", self.name, self.size);
+        for (i, service) in self.size.services().iter().enumerate() {
+            println!("{}. {}", i + 1, service);
+        }
+        println!("visit <inn/shop/forge/board> or leave");
+    }
+}
@@ -0,0 +1,323 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::content::{self, ContentError, Entry, Section, Writer};
+use crate::world::World;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Weather {
+    Clear,
+    Rain,
+    Fog,
+    Storm,
+    Snow,
+}
+
+impl Weather {
+    pub const ALL: [Weather; 5] = [Weather::Clear, Weather::Rain, Weather::Fog, Weather::Storm, Weather::Snow];
+
+    pub fn parse(value: &str) -> Option<Weather> {
+        return Weather::ALL.into_iter().find(|w| w.to_string() == value);
+    }
+
+    // tiles taken off how far the player can see
+    pub fn sight_penalty(&self) -> usize {
+        use Weather::*;
+        match self {
+            Clear => return 0,
+            Rain | Storm | Snow => return 1,
+            Fog => return 2,
+        }
+    }
+
+    // extra turns every move takes
+    pub fn slowdown(&self) -> u64 {
+        use Weather::*;
+        match self {
+            Storm | Snow => return 1,
+            _ => return 0,
+        }
+    }
+
+    // for forecasts, "expect a storm"
+    pub fn noun(&self) -> &'static str {
+        use Weather::*;
+        match self {
+            Clear => return "clear skies",
+            Rain => return "rain",
+            Fog => return "fog",
+            Storm => return "a storm",
+            Snow => return "snow",
+        }
+    }
+
+    // whether being out in it soaks the player
+    pub fn is_wet(&self) -> bool {
+        return matches!(self, Weather::Rain | Weather::Storm);
+    }
+
+    // added to the description of wherever the player is
+    pub fn describe(&self) -> Option<&'static str> {
+        use Weather::*;
+        match self {
+            Clear => return None,
+            Rain => return Some("Rain patters down all around you."),
+            Fog => return Some("A thick fog hangs over everything."),
+            Storm => return Some("Thunder rolls as the storm lashes the land."),
+            Snow => return Some("Snow falls thick and silent."),
+        }
+    }
+}
+
+impl Display for Weather {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use Weather::*;
+        match self {
+            Clear => write!(f, "clear"),
+            Rain => write!(f, "rain"),
+            Fog => write!(f, "fog"),
+            Storm => write!(f, "storm"),
+            Snow => write!(f, "snow"),
+        }
+    }
+}
+
+// an area of the map with its own kinds of weather
+#[derive(Clone, Debug)]
+pub struct Climate {
+    pub id: String,
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+    // weather and weight of it forming here, clear weather forms nothing
+    pub weather: Vec<(Weather, i32)>,
+    // tiles a front moves each step, in x and y
+    pub wind: (i32, i32),
+    // rain and storms fall as snow
+    pub cold: bool,
+}
+
+impl Climate {
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        return x >= self.from.0 && x <= self.to.0 && y >= self.from.1 && y <= self.to.1;
+    }
+}
+
+// a patch of weather drifting across the map
+#[derive(Clone, Debug)]
+pub struct Front {
+    pub weather: Weather,
+    pub pos: (i32, i32),
+    pub radius: i32,
+    pub wind: (i32, i32),
+    // turns before it blows itself out
+    pub turns: u64,
+}
+
+impl Front {
+    pub fn covers(&self, x: usize, y: usize) -> bool {
+        let (dx, dy) = (x as i32 - self.pos.0, y as i32 - self.pos.1);
+        return dx * dx + dy * dy <= self.radius * self.radius;
+    }
+
+    // where it will be after moving `steps` times
+    pub fn ahead(&self, steps: i32) -> Front {
+        let mut front = self.clone();
+        front.pos = (self.pos.0 + self.wind.0 * steps, self.pos.1 + self.wind.1 * steps);
+        return front;
+    }
+
+    pub fn save(&self, out: &mut Writer, index: usize) {
+        out.section("front", index);
+        out.entry("weather", self.weather);
+        out.entry("pos", format!("{} {}", self.pos.0, self.pos.1));
+        out.entry("radius", self.radius);
+        out.entry("wind", format!("{} {}", self.wind.0, self.wind.1));
+        out.entry("turns", self.turns);
+    }
+
+    pub fn load(section: &Section) -> Result<Front, ContentError> {
+        let entry = section.require("weather")?;
+        let weather = Weather::parse(&entry.value).ok_or(ContentError::InvalidValue(entry.line, entry.value.clone()))?;
+        let radius = section.require("radius")?;
+        let turns = section.require("turns")?;
+        return Ok(Front {
+            weather,
+            pos: parse_pair(section.require("pos")?)?,
+            radius: radius.value.parse().ok().filter(|r| *r > 0).ok_or(ContentError::InvalidValue(radius.line, radius.value.clone()))?,
+            wind: parse_pair(section.require("wind")?)?,
+            turns: turns.value.parse().map_err(|_| ContentError::InvalidValue(turns.line, turns.value.clone()))?,
+        });
+    }
+}
+
+// `<x> <y>`, either of which can be negative
+fn parse_pair(entry: &Entry) -> Result<(i32, i32), ContentError> {
+    match entry.value.split_once(' ').map(|(x, y)| (x.trim().parse(), y.trim().parse())) {
+        Some((Ok(x), Ok(y))) => return Ok((x, y)),
+        _ => return Err(ContentError::InvalidValue(entry.line, entry.value.clone())),
+    }
+}
+
+fn parse_corner(section: &Section, key: &str, world: &World) -> Result<(usize, usize), ContentError> {
+    let entry = section.require(key)?;
+    match parse_pair(entry)? {
+        (x, y) if world.in_bounds(x, y) => return Ok((x as usize, y as usize)),
+        _ => return Err(ContentError::UnknownTile(entry.line, entry.value.clone())),
+    }
+}
+
+fn parse_climate(section: &Section, world: &World, errors: &mut Vec<ContentError>) -> Option<Climate> {
+    let error_count = errors.len();
+
+    let mut weather = vec!();
+    for entry in section.all("weather") {
+        let args: Vec<&str> = entry.value.split_whitespace().collect();
+        let parsed = match args.as_slice() {
+            [name] => Weather::parse(name).map(|w| (w, 1)),
+            [name, weight] => Weather::parse(name).zip(weight.parse().ok().filter(|w| *w > 0)),
+            _ => None,
+        };
+        match parsed {
+            Some(found) => weather.push(found),
+            None => errors.push(ContentError::InvalidValue(entry.line, entry.value.clone())),
+        }
+    }
+    if section.get("weather").is_none() {
+        errors.push(ContentError::MissingKey(section.line, "weather".to_string()));
+    }
+
+    let wind = match section.get("wind") {
+        Some(entry) => match parse_pair(entry) {
+            Ok((x, y)) if x.abs() <= 1 && y.abs() <= 1 => (x, y),
+            _ => {
+                errors.push(ContentError::InvalidValue(entry.line, entry.value.clone()));
+                (0, 0)
+            },
+        },
+        None => (1, 0),
+    };
+
+    let from = parse_corner(section, "from", world);
+    let to = parse_corner(section, "to", world);
+    match (from, to) {
+        (Ok(from), Ok(to)) if errors.len() == error_count => return Some(Climate {
+            id: section.id.clone(),
+            // either pair of opposite corners will do
+            from: (from.0.min(to.0), from.1.min(to.1)),
+            to: (from.0.max(to.0), from.1.max(to.1)),
+            weather,
+            wind,
+            cold: section.get("cold").is_some_and(|e| e.value == "true"),
+        }),
+        (from, to) => {
+            errors.extend(from.err());
+            errors.extend(to.err());
+            return None;
+        },
+    }
+}
+
+// loads the climates weather forms in, later ones take precedence where they overlap
+pub fn load_climates(path: &str, world: &World) -> Result<Vec<Climate>, Vec<ContentError>> {
+    let sections = content::load(path).map_err(|err| vec!(err))?;
+    let mut errors = vec!();
+    let mut climates: Vec<Climate> = vec!();
+
+    for section in sections.iter() {
+        if section.kind != "climate" {
+            errors.push(ContentError::InvalidValue(section.line, section.kind.clone()));
+            continue;
+        }
+        if climates.iter().any(|c| c.id == section.id) {
+            errors.push(ContentError::Duplicate(section.line, section.id.clone()));
+            continue;
+        }
+        if let Some(climate) = parse_climate(section, world, &mut errors) {
+            climates.push(climate);
+        }
+    }
+
+    if errors.is_empty() && climates.is_empty() {
+        errors.push(ContentError::MissingKey(0, "climate".to_string()));
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    return Ok(climates);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(src: &str) -> Section {
+        return content::parse(src).unwrap().remove(0);
+    }
+
+    fn front(pos: (i32, i32), radius: i32, wind: (i32, i32)) -> Front {
+        return Front { weather: Weather::Rain, pos, radius, wind, turns: 10 };
+    }
+
+    #[test]
+    fn parses_climates() {
+        let mut errors = vec!();
+        let climate = parse_climate(&section("[climate peaks]\nfrom = 30 20\nto = 10 5\nweather = snow 3\nweather = fog\nwind = -1 1\ncold = true\n"), &World::test_map(), &mut errors).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!((climate.from, climate.to), ((10, 5), (30, 20)));
+        assert_eq!(climate.weather, vec!((Weather::Snow, 3), (Weather::Fog, 1)));
+        assert_eq!(climate.wind, (-1, 1));
+        assert!(climate.cold && climate.contains(30, 5));
+    }
+
+    #[test]
+    fn climates_need_positive_weights_and_gentle_wind() {
+        let mut errors = vec!();
+        let climate = parse_climate(&section("[climate bad]\nfrom = 1 1\nto = 5 5\nweather = rain 0\nweather = hail\nweather = fog -2\nwind = 2 0\n"), &World::test_map(), &mut errors);
+        assert!(climate.is_none());
+        assert_eq!(errors, vec!(
+            ContentError::InvalidValue(4, "rain 0".to_string()),
+            ContentError::InvalidValue(5, "hail".to_string()),
+            ContentError::InvalidValue(6, "fog -2".to_string()),
+            ContentError::InvalidValue(7, "2 0".to_string()),
+        ));
+
+        let mut errors = vec!();
+        assert!(parse_climate(&section("[climate dry]\nfrom = 1 1\nto = 0 5\n"), &World::test_map(), &mut errors).is_none());
+        assert_eq!(errors, vec!(ContentError::MissingKey(1, "weather".to_string()), ContentError::UnknownTile(3, "0 5".to_string())));
+    }
+
+    #[test]
+    fn shipped_climates_load() {
+        assert!(!load_climates("./src/data/weather.txt", &World::test_map()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn fronts_cover_a_circle() {
+        let front = front((10, 10), 2, (1, 0));
+        assert!(front.covers(10, 10) && front.covers(12, 10) && front.covers(9, 11));
+        assert!(!front.covers(13, 10) && !front.covers(12, 12));
+    }
+
+    #[test]
+    fn fronts_move_with_the_wind() {
+        let front = front((10, 10), 2, (1, -1));
+        assert_eq!(front.ahead(3).pos, (13, 7));
+        assert_eq!(front.ahead(0).pos, front.pos);
+    }
+
+    #[test]
+    fn fronts_round_trip_through_saves() {
+        let mut out = Writer::default();
+        front((-3, 10), 4, (0, 1)).save(&mut out, 0);
+        let loaded = Front::load(&content::parse(&out.finish()).unwrap()[0]).unwrap();
+        assert_eq!((loaded.weather, loaded.pos, loaded.radius, loaded.wind, loaded.turns), (Weather::Rain, (-3, 10), 4, (0, 1), 10));
+    }
+
+    #[test]
+    fn fronts_need_a_radius() {
+        for radius in ["0", "-2"] {
+            let src = format!("[front 0]\nweather = rain\npos = 1 1\nradius = {}\nwind = 1 0\nturns = 5\n", radius);
+            assert_eq!(Front::load(&section(&src)).unwrap_err(), ContentError::InvalidValue(4, radius.to_string()));
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::io::stdout;
 use image::{io::Reader as ImageReader, Pixel};
 use std::fmt::{self, Display, Formatter};
@@ -7,14 +8,47 @@ use crossterm::{
     terminal::{Clear, ClearType},
 };
 
+use crate::chest::Chest;
+use crate::clock::{self, Phase};
+use crate::content::{ContentError, Section, Writer};
+use crate::dialogue::{Dialogue, Location, Node, Npc};
+use crate::entity::Entity;
+use crate::inventory::Inventory;
+use crate::quest::{Quest, Trigger};
+use crate::rng::Rng;
+use crate::resource::Resource;
+use crate::town::{Town, TOWNS};
+use crate::weather::{Climate, Front, Weather};
+
+// turns between each move of a weather front
+const FRONT_STEP: u64 = 3;
+// most fronts over the map at once and the percent chance of a new one forming each turn
+const MAX_FRONTS: usize = 8;
+const FRONT_CHANCE: i32 = 5;
+
 #[derive(Debug)]
 pub struct World {
     tiles: Box<[Box<[Tile]>]>,
     max_x: u8,
+    quests: Vec<Quest>,
+    npcs: Vec<Npc>,
+    dialogue: Dialogue,
+    chests: Vec<Chest>,
+    // uses left and the turn they were counted at, for resources that have been worked, by tile
+    harvested: HashMap<(usize, usize, String), (u32, u64)>,
+    // tiles the player has changed since the map was loaded
+    altered: Vec<(usize, usize)>,
+    campfires: Vec<(usize, usize)>,
+    entities: Vec<Entity>,
+    climates: Vec<Climate>,
+    fronts: Vec<Front>,
+    // turns passed since the start of the game
+    turn: u64,
 }
 
 impl World {
-    pub fn new(src: String) -> World {
+    // every town on the map has to be named in `TOWNS`, and every named town has to be on the map
+    pub fn new(src: String) -> Result<World, Vec<ContentError>> {
         let img = ImageReader::open(src).unwrap().decode().unwrap();
         let mut tiles = World::default().tiles;
         let img = img.into_rgb8();
@@ -25,11 +59,123 @@ impl World {
                 tiles[y as usize][x as usize] = Tile::new((rgb[0], rgb[1], rgb[2]));
             };
         };
-        
-        return World {
-            tiles: tiles,
+
+        let mut errors = vec!();
+        for (y, row) in tiles.iter_mut().enumerate() {
+            for (x, tile) in row.iter_mut().enumerate().filter(|(_, tile)| tile.name == TileName::Town) {
+                match TOWNS.iter().find(|t| (t.0, t.1) == (x, y)) {
+                    Some(&(_, _, name, size)) => *tile = Tile::town(Town::new(name, size)),
+                    None => errors.push(ContentError::UnnamedTown(x, y)),
+                }
+            }
+        }
+        for (x, y, name, _) in TOWNS {
+            if tiles[y][x].town.is_none() {
+                errors.push(ContentError::MisplacedTown(name.to_string()));
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        return Ok(World {
+            tiles,
             max_x: 200,
-        };
+            quests: vec!(),
+            npcs: vec!(),
+            dialogue: HashMap::new(),
+            chests: vec!(),
+            harvested: HashMap::new(),
+            altered: vec!(),
+            campfires: vec!(),
+            entities: vec!(),
+            climates: vec!(),
+            fronts: vec!(),
+            turn: 0,
+        });
+    }
+
+    // the real map, for tests that check content against it
+    #[cfg(test)]
+    pub fn test_map() -> World {
+        return World::new("./src/img/text-map.png".to_string()).unwrap();
+    }
+
+    // pins board quests to their town and keeps the rest until their trigger fires
+    pub fn post_quests(&mut self, quests: Vec<Quest>) {
+        for quest in quests {
+            match &quest.trigger {
+                Trigger::Board(town_name) => {
+                    let town_name = town_name.clone();
+                    if let Some(town) = self.towns_mut().find(|t| t.name == town_name) {
+                        town.quests.push(quest);
+                    }
+                },
+                _ => self.quests.push(quest),
+            }
+        }
+    }
+
+    // removes a quest from wherever it is waiting, whether or not it is unlocked
+    pub fn take_quest(&mut self, quest_id: &str) -> Option<Quest> {
+        if let Some(i) = self.quests.iter().position(|q| q.id == quest_id) {
+            return Some(self.quests.remove(i));
+        }
+        for town in self.towns_mut() {
+            if let Some(i) = town.quests.iter().position(|q| q.id == quest_id) {
+                return Some(town.quests.remove(i));
+            }
+        }
+        return None;
+    }
+
+    pub fn add_npcs(&mut self, npcs: Vec<Npc>, dialogue: Dialogue) {
+        self.npcs.extend(npcs);
+        self.dialogue.extend(dialogue);
+    }
+
+    pub fn find_npc(&self, name: &str) -> Option<&Npc> {
+        return self.npcs.iter().find(|n| n.id.eq_ignore_ascii_case(name) || n.name.eq_ignore_ascii_case(name));
+    }
+
+    // npcs standing on the tile, or inside the town when `town` is given
+    pub fn npcs_at(&self, x: usize, y: usize, town: Option<&str>) -> Vec<&Npc> {
+        return self.npcs.iter().filter(|npc| match (&npc.location, town) {
+            (Location::Town(name), Some(town)) => name == town,
+            (Location::Tile(nx, ny), None) => (*nx, *ny) == (x, y),
+            _ => false,
+        }).collect();
+    }
+
+    pub fn add_chests(&mut self, chests: Vec<Chest>) {
+        self.chests.extend(chests);
+    }
+
+    pub fn chest_at(&self, x: usize, y: usize) -> Option<&Chest> {
+        return self.chests.iter().find(|c| c.pos == (x, y));
+    }
+
+    pub fn chest_at_mut(&mut self, x: usize, y: usize) -> Option<&mut Chest> {
+        return self.chests.iter_mut().find(|c| c.pos == (x, y));
+    }
+
+    pub fn dialogue_node(&self, id: &str) -> &Node {
+        return &self.dialogue[id];
+    }
+
+    // removes and returns every unlocked quest whose trigger the player has just met
+    pub fn fire_triggers(&mut self, pos: (usize, usize), town: Option<&str>) -> Vec<Quest> {
+        let tile = self.tile_name(pos.0, pos.1);
+        let (fired, waiting) = self.quests.drain(..).partition(|quest| {
+            quest.unlocked && match &quest.trigger {
+                Trigger::EnterTown(name) => town == Some(name.as_str()),
+                Trigger::VisitTile { x, y } => (*x, *y) == pos,
+                Trigger::ReachRegion(region) => *region == tile,
+                Trigger::Board(_) | Trigger::Dialogue => false,
+            }
+        });
+        self.quests = waiting;
+        return fired;
     }
 
     pub fn print_map(&self) {
@@ -58,6 +204,16 @@ impl World {
             println!("│");
         }
 
+        for (x, y) in self.campfires.iter() {
+            execute!(stdout(), MoveTo((x + 1) as u16, (y + 1) as u16)).unwrap();
+            print!("♨");
+        }
+
+        for entity in self.entities.iter().filter(|e| e.is_active(self.turn)) {
+            execute!(stdout(), MoveTo((entity.pos.0 + 1) as u16, (entity.pos.1 + 1) as u16)).unwrap();
+            print!("{}", entity.symbol);
+        }
+
         print!("└");
 
         for _ in 0..self.max_x {
@@ -66,15 +222,24 @@ impl World {
         println!("┘");
     }
 
-    pub fn discover(&mut self, x:usize, y:usize, display:bool) {
+    // reveals every tile within `sight` steps, returns how many of them had not been seen before
+    pub fn discover(&mut self, x:usize, y:usize, display:bool, sight:usize) -> u32 {
+        let mut nearby = vec!();
+        for ny in y.saturating_sub(sight)..=y + sight {
+            for nx in x.saturating_sub(sight)..=x + sight {
+                if nx.abs_diff(x) + ny.abs_diff(y) <= sight && ny < self.tiles.len() && nx < self.tiles[ny].len() {
+                    nearby.push((nx, ny));
+                }
+            }
+        }
+        let found = nearby.iter().filter(|(x, y)| !self.tiles[*y][*x].discovered).count() as u32;
         self.tiles[y][x].discovered = true;
 
         if !display {
-            self.tiles[y+1][x].discovered = true;
-            self.tiles[y-1][x].discovered = true;
-            self.tiles[y][x+1].discovered = true;
-            self.tiles[y][x-1].discovered = true;
-            return;
+            for (x, y) in nearby {
+                self.tiles[y][x].discovered = true;
+            }
+            return found;
         }
 
         use TileName::*;
@@ -84,12 +249,48 @@ impl World {
             Trees => "You see a few trees scattered around.",
             Forest => "You see trees all around you.",
             Hills => "You see rolling hills.",
+            Town => "You stand before the gates of a town.",
+            Bridge => "You stand on a bridge of lashed logs.",
             _ => "How did you manage to get here?",
         };
 
         println!("{}", msg);
+        if let Some(msg) = self.phase().describe() {
+            println!("{}", msg);
+        }
+        if let Some(msg) = self.weather_at(x, y).describe() {
+            println!("{}", msg);
+        }
+
+        for npc in self.npcs_at(x, y, None) {
+            println!("{} is here.", npc.name);
+        }
+
+        // npcs that roam were already named above
+        for entity in self.entities_at(x, y).into_iter().filter(|e| e.npc.is_none()) {
+            println!("{} is here.", entity.describe());
+        }
 
-        if self.tiles[y+1][x].discovered != true {
+        if self.has_campfire(x, y) {
+            println!("A campfire crackles here.");
+        }
+
+        if let Some(chest) = self.chest_at(x, y) {
+            let name = chest.name.to_lowercase();
+            let article = if name.starts_with(['a', 'e', 'i', 'o', 'u']) { "an" } else { "a" };
+            println!("There is {} {} here.", article, name);
+        }
+
+        if !self.tiles[y][x].items.is_empty() {
+            println!("On the ground: {}", self.tiles[y][x].items.summary());
+        }
+
+        if sight == 0 {
+            println!("You can't make out anything further.");
+            return found;
+        }
+
+        if !self.tiles[y+1][x].discovered {
             self.tiles[y+1][x].discovered = true;
 
             use TileName::*;
@@ -103,12 +304,13 @@ impl World {
                 Hills => "You see rolling hills",
                 Mountain => "You see a mountain",
                 Town => "You see a town",
+                Bridge => "You see a bridge",
             };
 
-            println!("{} to the North", msg);
+            println!("{} to the South.", msg);
         }
 
-        if self.tiles[y-1][x].discovered != true {
+        if !self.tiles[y-1][x].discovered {
             self.tiles[y-1][x].discovered = true;
 
             use TileName::*;
@@ -122,12 +324,13 @@ impl World {
                 Hills => "You see rolling hills",
                 Mountain => "You see a mountain",
                 Town => "You see a town",
+                Bridge => "You see a bridge",
             };
 
-            println!("{} to the South.", msg);
+            println!("{} to the North.", msg);
         }
 
-        if self.tiles[y][x+1].discovered != true {
+        if !self.tiles[y][x+1].discovered {
             self.tiles[y][x+1].discovered = true;
 
             use TileName::*;
@@ -141,12 +344,13 @@ impl World {
                 Hills => "You see rolling hills",
                 Mountain => "You see a mountain",
                 Town => "You see a town",
+                Bridge => "You see a bridge",
             };
 
-            println!("{} to the East", msg);
+            println!("{} to the East.", msg);
         }
 
-        if self.tiles[y][x-1].discovered != true {
+        if !self.tiles[y][x-1].discovered {
             self.tiles[y][x-1].discovered = true;
 
             use TileName::*;
@@ -160,17 +364,22 @@ impl World {
                 Hills => "You see rolling hills",
                 Mountain => "You see a mountain",
                 Town => "You see a town",
+                Bridge => "You see a bridge",
             };
 
-            println!("{} to the West", msg);
+            println!("{} to the West.", msg);
         }
 
-        return;
+        // anything further away is only marked on the map
+        for (x, y) in nearby {
+            self.tiles[y][x].discovered = true;
+        }
+        return found;
     }
 
     pub fn display_tile(&self, x: usize, y: usize) {
         use TileName::*;
-        let msg = match self.tiles[x][y].name {
+        let msg = match self.tiles[y][x].name {
             Ocean => "You see the ocean",
             Water => "You see running water",
             Sand => "You see wet sand",
@@ -180,9 +389,374 @@ impl World {
             Hills => "You see rolling hills",
             Mountain => "You see a mountain",
             Town => "You see a town",
+            Bridge => "You see a bridge",
         };
 
         println!("{}.", msg);
+        for entity in self.entities_at(x, y) {
+            println!("{} is there.", entity.describe());
+        }
+    }
+
+    // places roaming entities, moving their npcs out onto the map with them
+    pub fn add_entities(&mut self, entities: Vec<Entity>) {
+        for entity in entities {
+            if let Some(id) = &entity.npc {
+                self.move_npc(id, entity.pos);
+            }
+            self.entities.push(entity);
+        }
+    }
+
+    fn move_npc(&mut self, id: &str, (x, y): (usize, usize)) {
+        if let Some(npc) = self.npcs.iter_mut().find(|n| n.id == id) {
+            npc.location = Location::Tile(x, y);
+        }
+    }
+
+    pub fn entities_at(&self, x: usize, y: usize) -> Vec<&Entity> {
+        return self.entities.iter().filter(|e| e.pos == (x, y) && e.is_active(self.turn)).collect();
+    }
+
+    pub fn entity_mut(&mut self, id: &str) -> Option<&mut Entity> {
+        return self.entities.iter_mut().find(|e| e.id == id);
+    }
+
+    // moves every entity one step, `player` is `None` while they are out of sight in a town
+    pub fn move_entities(&mut self, player: Option<(usize, usize)>, rng: &mut Rng) {
+        let mut entities = std::mem::take(&mut self.entities);
+        let turn = self.turn;
+        for entity in entities.iter_mut().filter(|e| e.is_active(turn)) {
+            entity.step(self, player, rng);
+            if let Some(id) = &entity.npc {
+                self.move_npc(id, entity.pos);
+            }
+        }
+        self.entities = entities;
+    }
+
+    // the first tile on a shortest path from `from` to `to` over tiles `can_enter` allows
+    pub fn step_towards(&self, from: (usize, usize), to: (usize, usize), can_enter: impl Fn(usize, usize) -> bool) -> Option<(usize, usize)> {
+        if from == to {
+            return None;
+        }
+
+        // searched backwards from the goal, so the tile `from` is first reached from is the step to take
+        let mut seen = HashSet::from([to]);
+        let mut queue = VecDeque::from([to]);
+        while let Some((x, y)) = queue.pop_front() {
+            for (nx, ny) in [(x, y - 1), (x + 1, y), (x, y + 1), (x - 1, y)] {
+                if (nx, ny) == from {
+                    return Some((x, y));
+                }
+                if seen.contains(&(nx, ny)) || !can_enter(nx, ny) {
+                    continue;
+                }
+                seen.insert((nx, ny));
+                queue.push_back((nx, ny));
+            }
+        }
+        return None;
+    }
+
+    pub fn turn(&self) -> u64 {
+        return self.turn;
+    }
+
+    pub fn phase(&self) -> Phase {
+        return clock::phase(self.turn);
+    }
+
+    pub fn add_climates(&mut self, climates: Vec<Climate>) {
+        self.climates = climates;
+    }
+
+    fn climate_at(&self, x: usize, y: usize) -> Option<&Climate> {
+        return self.climates.iter().rev().find(|c| c.contains(x, y));
+    }
+
+    // rain and storms fall as snow where it is cold, and snow melts to rain where it isn't
+    fn settle(&self, weather: Weather, x: usize, y: usize) -> Weather {
+        let cold = self.climate_at(x, y).is_some_and(|c| c.cold);
+        match weather {
+            Weather::Rain | Weather::Storm if cold => return Weather::Snow,
+            Weather::Snow if !cold => return Weather::Rain,
+            weather => return weather,
+        }
+    }
+
+    pub fn weather_at(&self, x: usize, y: usize) -> Weather {
+        let weather = self.fronts.iter().find(|f| f.covers(x, y)).map_or(Weather::Clear, |f| f.weather);
+        return self.settle(weather, x, y);
+    }
+
+    // the weather expected over a tile for each of the next `hours`, starting with now
+    pub fn forecast(&self, x: usize, y: usize, hours: u64) -> Vec<Weather> {
+        return (0..=hours).map(|hour| {
+            let turns = hour * clock::TURNS_PER_HOUR;
+            // fronts move on every multiple of FRONT_STEP, counted from the current turn
+            let steps = ((self.turn + turns) / FRONT_STEP - self.turn / FRONT_STEP) as i32;
+            let weather = self.fronts.iter()
+                .filter(|f| f.turns > turns)
+                .map(|f| f.ahead(steps))
+                .find(|f| f.covers(x, y))
+                .map_or(Weather::Clear, |f| f.weather);
+            self.settle(weather, x, y)
+        }).collect();
+    }
+
+    // blows every front along for one turn and now and then forms a new one
+    pub fn update_weather(&mut self, rng: &mut Rng) {
+        let step = self.turn.is_multiple_of(FRONT_STEP);
+        for front in self.fronts.iter_mut() {
+            front.turns = front.turns.saturating_sub(1);
+            if step {
+                *front = front.ahead(1);
+            }
+        }
+        let (width, height) = (self.max_x as i32, self.tiles.len() as i32);
+        self.fronts.retain(|f| {
+            let on_map = f.pos.0 + f.radius >= 0 && f.pos.0 - f.radius < width && f.pos.1 + f.radius >= 0 && f.pos.1 - f.radius < height;
+            f.turns > 0 && on_map
+        });
+
+        if self.fronts.len() >= MAX_FRONTS || rng.range(1, 100) > FRONT_CHANCE {
+            return;
+        }
+        let (x, y) = (rng.range(1, width - 2) as usize, rng.range(1, height - 2) as usize);
+        let Some(climate) = self.climate_at(x, y) else {
+            return;
+        };
+        let total: i32 = climate.weather.iter().map(|(_, weight)| weight).sum();
+        if total == 0 {
+            return;
+        }
+        let mut roll = rng.range(1, total);
+        let (weather, _) = climate.weather.iter().find(|(_, weight)| {
+            roll -= weight;
+            roll <= 0
+        }).unwrap();
+        if *weather == Weather::Clear {
+            return;
+        }
+        let front = Front {
+            weather: *weather,
+            pos: (x as i32, y as i32),
+            radius: rng.range(6, 14),
+            wind: climate.wind,
+            turns: rng.range(80, 240) as u64,
+        };
+        self.fronts.push(front);
+    }
+
+    pub fn advance(&mut self, turns: u64) {
+        self.turn += turns;
+    }
+
+    // uses of a resource left at a tile, counting what has grown back since it was last worked
+    pub fn resource_left(&self, x: usize, y: usize, resource: &Resource) -> u32 {
+        match self.harvested.get(&(x, y, resource.id.clone())) {
+            Some((left, since)) => {
+                let regrown = (self.turn - since) / resource.regrow;
+                return (*left as u64 + regrown).min(resource.amount as u64) as u32;
+            },
+            None => return resource.amount,
+        }
+    }
+
+    pub fn harvest(&mut self, x: usize, y: usize, resource: &Resource) {
+        let left = self.resource_left(x, y, resource).saturating_sub(1);
+        self.harvested.insert((x, y, resource.id.clone()), (left, self.turn));
+    }
+
+    // changes the terrain of a tile for good, it is kept in saves
+    pub fn set_tile(&mut self, x: usize, y: usize, name: TileName) {
+        self.tiles[y][x].accessible = name.passable();
+        self.tiles[y][x].name = name;
+        if !self.altered.contains(&(x, y)) {
+            self.altered.push((x, y));
+        }
+    }
+
+    pub fn has_campfire(&self, x: usize, y: usize) -> bool {
+        return self.campfires.contains(&(x, y));
+    }
+
+    pub fn add_campfire(&mut self, x: usize, y: usize) {
+        self.campfires.push((x, y));
+    }
+
+    // everything about the world that can differ from a freshly loaded one
+    pub fn save(&self, out: &mut Writer) {
+        out.section("world", "state");
+        out.entry("turn", self.turn);
+
+        out.section("map", "discovered");
+        for row in self.tiles.iter() {
+            let row: String = row.iter().map(|tile| if tile.discovered { '1' } else { '0' }).collect();
+            out.entry("row", row);
+        }
+
+        for (y, row) in self.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                let altered = self.altered.contains(&(x, y));
+                let campfire = self.has_campfire(x, y);
+                if !altered && !campfire && tile.items.is_empty() {
+                    continue;
+                }
+                out.section("tile", format!("{},{}", x, y));
+                if altered {
+                    out.entry("terrain", tile.name.label());
+                }
+                if campfire {
+                    out.entry("campfire", "yes");
+                }
+                tile.items.save(out);
+            }
+        }
+
+        for chest in self.chests.iter() {
+            out.section("chest", &chest.id);
+            out.entry("locked", chest.locked);
+            out.entry("open", chest.open);
+            chest.items.save(out);
+        }
+
+        for entity in self.entities.iter() {
+            entity.save(out);
+        }
+
+        for (index, front) in self.fronts.iter().enumerate() {
+            front.save(out, index);
+        }
+
+        out.section("harvest", "spots");
+        for ((x, y, id), (left, since)) in self.harvested.iter() {
+            out.entry("spot", format!("{} {} {} {} {}", x, y, id, left, since));
+        }
+    }
+
+    // applies a save on top of the world as built from the map and content files
+    pub fn load(&mut self, sections: &[Section]) -> Result<(), ContentError> {
+        for section in sections.iter() {
+            match section.kind.as_str() {
+                "world" => {
+                    let entry = section.require("turn")?;
+                    self.turn = entry.value.parse().map_err(|_| ContentError::InvalidValue(entry.line, entry.value.clone()))?;
+                },
+                "map" => {
+                    for (y, entry) in section.all("row").enumerate().take(self.tiles.len()) {
+                        for (x, flag) in entry.value.chars().enumerate().take(self.max_x as usize) {
+                            self.tiles[y][x].discovered = flag == '1';
+                        }
+                    }
+                },
+                "tile" => self.load_tile(section)?,
+                "chest" => {
+                    let chest = match self.chests.iter_mut().find(|c| c.id == section.id) {
+                        Some(chest) => chest,
+                        None => return Err(ContentError::InvalidValue(section.line, section.id.clone())),
+                    };
+                    chest.locked = section.require("locked")?.value == "true";
+                    chest.open = section.require("open")?.value == "true";
+                    chest.items = Inventory::load(section)?;
+                },
+                "entity" => {
+                    let mut entity = match self.entities.iter().find(|e| e.id == section.id) {
+                        Some(entity) => entity.clone(),
+                        None => return Err(ContentError::InvalidValue(section.line, section.id.clone())),
+                    };
+                    entity.load(section, self)?;
+                    if let Some(id) = &entity.npc {
+                        self.move_npc(id, entity.pos);
+                    }
+                    *self.entity_mut(&section.id).unwrap() = entity;
+                },
+                "front" => self.fronts.push(Front::load(section)?),
+                "harvest" => {
+                    for entry in section.all("spot") {
+                        let invalid = || ContentError::InvalidValue(entry.line, entry.value.clone());
+                        let args: Vec<&str> = entry.value.split_whitespace().collect();
+                        let [x, y, id, left, since] = args.as_slice() else {
+                            return Err(invalid());
+                        };
+                        let key = (x.parse().map_err(|_| invalid())?, y.parse().map_err(|_| invalid())?, id.to_string());
+                        let value = (left.parse().map_err(|_| invalid())?, since.parse().map_err(|_| invalid())?);
+                        self.harvested.insert(key, value);
+                    }
+                },
+                _ => {},
+            }
+        }
+        return Ok(());
+    }
+
+    fn load_tile(&mut self, section: &Section) -> Result<(), ContentError> {
+        let pos = section.id.split_once(',')
+            .and_then(|(x, y)| Some((x.parse::<i32>().ok()?, y.parse::<i32>().ok()?)))
+            .filter(|(x, y)| self.in_bounds(*x, *y));
+        let (x, y) = match pos {
+            Some((x, y)) => (x as usize, y as usize),
+            None => return Err(ContentError::UnknownTile(section.line, section.id.clone())),
+        };
+
+        if let Some(entry) = section.get("terrain") {
+            match TileName::parse(&entry.value) {
+                Some(name) => self.set_tile(x, y, name),
+                None => return Err(ContentError::UnknownTile(entry.line, entry.value.clone())),
+            }
+        }
+        if section.get("campfire").is_some() {
+            self.add_campfire(x, y);
+        }
+        self.tiles[y][x].items = Inventory::load(section)?;
+        return Ok(());
+    }
+
+    pub fn in_bounds(&self, x: i32, y: i32) -> bool {
+        // the outermost ring is kept clear so discovery can always look one tile further
+        return x > 0 && y > 0 && x < self.max_x as i32 - 1 && y < self.tiles.len() as i32 - 1;
+    }
+
+    // items lying on the ground at a tile
+    pub fn items_at_mut(&mut self, x: usize, y: usize) -> &mut Inventory {
+        return &mut self.tiles[y][x].items;
+    }
+
+    pub fn town_at(&self, x: usize, y: usize) -> Option<&Town> {
+        return self.tiles[y][x].town.as_ref();
+    }
+
+    pub fn town_at_mut(&mut self, x: usize, y: usize) -> Option<&mut Town> {
+        return self.tiles[y][x].town.as_mut();
+    }
+
+    pub fn towns_mut(&mut self) -> impl Iterator<Item = &mut Town> {
+        return self.tiles.iter_mut()
+            .flat_map(|row| row.iter_mut())
+            .filter_map(|tile| tile.town.as_mut());
+    }
+
+    pub fn tile_name(&self, x: usize, y: usize) -> TileName {
+        return self.tiles[y][x].name.clone();
+    }
+
+    // flips `unlocked` on every posted quest whose prerequisites are all completed
+    pub fn unlock_quests(&mut self, is_completed: impl Fn(&str) -> bool) {
+        let mut posted: Vec<&mut Quest> = self.quests.iter_mut().collect();
+        for row in self.tiles.iter_mut() {
+            for tile in row.iter_mut() {
+                if let Some(town) = tile.town.as_mut() {
+                    posted.extend(town.quests.iter_mut());
+                }
+            }
+        }
+
+        for quest in posted {
+            if !quest.unlocked && quest.prerequisites.iter().all(|id| is_completed(id)) {
+                quest.unlocked = true;
+            }
+        }
     }
 
     pub fn is_accessible(&self, x:usize, y:usize) -> bool {
@@ -198,12 +772,23 @@ impl Default for World {
         return World{
             tiles: vec![vec![Tile::default(); 200].into_boxed_slice(); 50].into_boxed_slice(),
             max_x: 200,
+            quests: vec!(),
+            npcs: vec!(),
+            dialogue: HashMap::new(),
+            chests: vec!(),
+            harvested: HashMap::new(),
+            altered: vec!(),
+            campfires: vec!(),
+            entities: vec!(),
+            climates: vec!(),
+            fronts: vec!(),
+            turn: 0,
         };
     }
 }
 
-#[derive(Clone, Debug)]
-enum TileName {
+#[derive(Clone, Debug, PartialEq)]
+pub enum TileName {
     Ocean,
     Water,
     Sand,
@@ -213,10 +798,59 @@ enum TileName {
     Hills,
     Mountain,
     Town,
+    Bridge,
     // Ferry,
     // Castle
 }
 
+impl TileName {
+    // accepts either the enum name or the label, ignoring case
+    pub fn parse(name: &str) -> Option<TileName> {
+        use TileName::*;
+        let tiles = [Ocean, Water, Sand, Plains, Trees, Forest, Hills, Mountain, Town, Bridge];
+        return tiles.into_iter().find(|tile| {
+            tile.label().eq_ignore_ascii_case(name) || format!("{:?}", tile).eq_ignore_ascii_case(name)
+        });
+    }
+
+    pub fn label(&self) -> &str {
+        use TileName::*;
+        match self {
+            Ocean => "ocean",
+            Water => "river",
+            Sand => "shore",
+            Plains => "plains",
+            Trees => "woods",
+            Forest => "forest",
+            Hills => "hills",
+            Mountain => "mountains",
+            Town => "town",
+            Bridge => "bridge",
+        }
+    }
+
+    // turns it takes to cross a tile of this kind on foot
+    pub fn move_cost(&self) -> u64 {
+        use TileName::*;
+        match self {
+            Forest | Hills => return 2,
+            _ => return 1,
+        }
+    }
+
+    // whether the player can stand on tiles of this kind
+    pub fn passable(&self) -> bool {
+        use TileName::*;
+        return !matches!(self, Ocean | Water | Sand | Mountain);
+    }
+
+    // whether the player can swim onto tiles of this kind when they can't walk there,
+    // only the shallows, the river itself still needs a bridge
+    pub fn swimmable(&self) -> bool {
+        return *self == TileName::Sand;
+    }
+}
+
 impl Display for TileName {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         use TileName::*;
@@ -230,6 +864,7 @@ impl Display for TileName {
             Hills => write!(f, "⏶"),
             Mountain => write!(f, "◮"),
             Town => write!(f, "⌂"),
+            Bridge => write!(f, "="),
             // Ferry => write!(f, "⛴"),
             // Castle => write!(f, "🏰"), //⛫
         }
@@ -242,6 +877,7 @@ struct Tile {
     accessible: bool,
     discovered: bool,
     town: Option<Town>,
+    items: Inventory,
 }
 
 impl Tile {
@@ -253,6 +889,7 @@ impl Tile {
                     accessible: false,
                     discovered: false,
                     town: None,
+                    items: Inventory::default(),
                 };
             },
             (255, 191, 0) => {
@@ -261,6 +898,7 @@ impl Tile {
                     accessible: true,
                     discovered: false,
                     town: None,
+                    items: Inventory::default(),
                 };
             },
             (0, 255, 0) => {
@@ -269,6 +907,7 @@ impl Tile {
                     accessible: true,
                     discovered: false,
                     town: None,
+                    items: Inventory::default(),
                 };
             },
             (0, 200, 0) => {
@@ -277,6 +916,7 @@ impl Tile {
                     accessible: true,
                     discovered: false,
                     town: None,
+                    items: Inventory::default(),
                 };
             },
             (0, 100, 0) => {
@@ -285,6 +925,7 @@ impl Tile {
                     accessible: true,
                     discovered: false,
                     town: None,
+                    items: Inventory::default(),
                 };
             },
             (100, 100, 255) => {
@@ -293,6 +934,7 @@ impl Tile {
                     accessible: false,
                     discovered: false,
                     town: None,
+                    items: Inventory::default(),
                 };
             },
             (0, 0, 200) => {
@@ -301,6 +943,7 @@ impl Tile {
                     accessible: false,
                     discovered: false,
                     town: None,
+                    items: Inventory::default(),
                 };
             },
             (0, 0, 100) => {
@@ -309,6 +952,7 @@ impl Tile {
                     accessible: false,
                     discovered: false,
                     town: None,
+                    items: Inventory::default(),
                 };
             },
             (255, 255, 255) => {
@@ -317,6 +961,7 @@ impl Tile {
                     accessible: true,
                     discovered: false,
                     town: None,
+                    items: Inventory::default(),
                 };
             },
             _ => {
@@ -324,6 +969,16 @@ impl Tile {
             }
         }
     }
+
+    fn town(town: Town) -> Tile {
+        return Tile {
+            name: TileName::Town,
+            accessible: true,
+            discovered: false,
+            town: Some(town),
+            items: Inventory::default(),
+        };
+    }
 }
 
 impl Default for Tile {
@@ -332,29 +987,88 @@ impl Default for Tile {
             name: TileName::Plains,
             accessible: true,
             discovered: false,
-            town: None
+            town: None,
+            items: Inventory::default(),
         }
     }
 }
 
-#[derive(Copy, Clone, Debug)]
-enum TownSize {
-    Capital,
-    City,
-    Village,
-    Hut,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content;
+    use crate::item::{self, Item};
 
-#[derive(Clone, Debug)]
-struct Town {
-    name: String,
-    size: TownSize,
-    quests: Vec<Quest>,
-}
+    fn windy_world(turn: u64) -> World {
+        return World {
+            fronts: vec!(Front { weather: Weather::Rain, pos: (20, 10), radius: 2, wind: (1, 0), turns: 100 }),
+            turn,
+            ..World::default()
+        };
+    }
 
-#[derive(Clone, Debug)]
-struct Quest {
-    name: String,
-    unlocked: bool,
-    completed: bool,
-}
\ No newline at end of file
+    #[test]
+    fn changes_round_trip_through_saves() {
+        item::load_test_catalogue();
+        let mut world = World::test_map();
+        world.advance(42);
+        world.tiles[12][34].discovered = true;
+        world.set_tile(66, 20, TileName::Bridge);
+        world.add_campfire(30, 30);
+        world.items_at_mut(31, 30).add(Item::new("rope").unwrap());
+        world.harvested.insert((5, 6, "wood".to_string()), (2, 40));
+
+        let mut out = Writer::default();
+        world.save(&mut out);
+        let mut loaded = World::test_map();
+        loaded.load(&content::parse(&out.finish()).unwrap()).unwrap();
+
+        assert_eq!(loaded.turn, 42);
+        assert!(loaded.tiles[12][34].discovered && !loaded.tiles[12][35].discovered);
+        assert_eq!(loaded.tile_name(66, 20), TileName::Bridge);
+        assert!(loaded.is_accessible(66, 20));
+        assert!(loaded.has_campfire(30, 30) && !loaded.has_campfire(31, 30));
+        assert_eq!(loaded.items_at_mut(31, 30).count("rope"), 1);
+        assert_eq!(loaded.harvested[&(5, 6, "wood".to_string())], (2, 40));
+    }
+
+    #[test]
+    fn every_town_is_on_the_map() {
+        let world = World::test_map();
+        for (x, y, name, _) in TOWNS {
+            assert_eq!(world.town_at(x, y).map(|t| t.name.as_str()), Some(name));
+        }
+    }
+
+    #[test]
+    fn forecast_follows_the_front() {
+        let world = windy_world(0);
+        assert_eq!(world.forecast(20, 10, 0), vec!(Weather::Rain));
+        assert_eq!(world.forecast(26, 10, 3), vec!(Weather::Clear, Weather::Clear, Weather::Rain, Weather::Rain));
+    }
+
+    #[test]
+    fn forecast_matches_the_weather_that_comes() {
+        // starting between steps, so the first one comes sooner than a full step
+        for start in 0..FRONT_STEP {
+            let mut world = windy_world(start);
+            let (x, y) = (25, 10);
+            let forecast = world.forecast(x, y, 4);
+            let mut rng = Rng::new(1);
+            for (hour, expected) in forecast.into_iter().enumerate() {
+                assert_eq!(world.weather_at(x, y), expected, "start {} hour {}", start, hour);
+                for _ in 0..clock::TURNS_PER_HOUR {
+                    world.advance(1);
+                    world.update_weather(&mut rng);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn forecast_ends_when_the_front_blows_out() {
+        let mut world = windy_world(0);
+        world.fronts[0].turns = clock::TURNS_PER_HOUR;
+        assert_eq!(world.forecast(21, 10, 1), vec!(Weather::Rain, Weather::Clear));
+    }
+}